@@ -0,0 +1,582 @@
+use anyhow::{Context, Result, bail};
+use season_data::core::{
+    ConfirmStatus, DEFAULT_VERIFY_CACHE_MAX_AGE, DEFAULT_VERIFY_CONCURRENCY, SeasonRange,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 命令行参数
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    /// LLM 费用预算（与 `LLM_PRICE_*` 同一币种），用完后剩余条目保留候选
+    pub llm_budget: Option<f64>,
+    /// 忽略已保存的模型判断，重新请求
+    pub llm_refresh: bool,
+    /// 记录续播番组（仅用于更新季度数据）
+    pub carry_over: bool,
+    /// 要更新的季度范围（仅用于更新季度数据），如 `2020-winter..2025-fall`
+    pub seasons: Option<SeasonRange>,
+    /// 按当前日期更新上一、当前与下一季度（仅用于更新季度数据）
+    pub recent: bool,
+    /// 更新后获取已确认条目的 Bangumi 详情，写入 `-enriched.json`（仅用于更新季度数据）
+    pub enrich: bool,
+    /// 与 Bangumi 每日放送对照当前季度，写入 `-calendar.json`（仅用于更新季度数据）
+    pub calendar: bool,
+    pub command: Command,
+}
+
+/// 发布数据目录默认值
+const DEFAULT_RELEASE_DIR: &str = "release";
+
+/// 子命令
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// 更新季度数据（默认）
+    Update(UpdateArgs),
+    /// 以发布数据中已确认的条目为真值评估匹配流程
+    Eval(EvalArgs),
+    /// 重跑由旧版本提示模板判断的条目
+    Rematch(RematchArgs),
+    /// 核对已确认映射的 Bangumi 条目是否已删除、合并、类型变更或锁定
+    Verify(VerifyArgs),
+}
+
+/// 更新季度数据的参数
+#[derive(Debug, PartialEq)]
+pub struct UpdateArgs {
+    /// 发布数据目录
+    pub release_dir: PathBuf,
+}
+
+/// `eval` 子命令参数
+#[derive(Debug, PartialEq)]
+pub struct EvalArgs {
+    /// 发布数据目录
+    pub release_dir: PathBuf,
+    /// 样本数
+    pub sample: usize,
+    /// 抽样种子
+    pub seed: u64,
+    /// 作为真值的状态
+    pub statuses: Vec<ConfirmStatus>,
+    /// API 响应缓存目录
+    pub cache_dir: PathBuf,
+    /// 只使用缓存的响应，不发送请求
+    pub replay: bool,
+    /// JSON 报告输出路径
+    pub output: Option<PathBuf>,
+}
+
+/// `rematch` 子命令参数
+#[derive(Debug, PartialEq)]
+pub struct RematchArgs {
+    /// 发布数据目录
+    pub release_dir: PathBuf,
+}
+
+/// `verify` 子命令参数
+#[derive(Debug, PartialEq)]
+pub struct VerifyArgs {
+    /// 发布数据目录
+    pub release_dir: PathBuf,
+    /// API 响应缓存目录（不指定时不缓存）
+    pub cache_dir: Option<PathBuf>,
+    /// 缓存的有效期，以内的条目不再请求（中断后继续运行）
+    pub cache_max_age: Duration,
+    /// 同时进行的请求数
+    pub concurrency: usize,
+    /// 将有问题的条目重置为未确认并写回发布数据
+    pub reset: bool,
+    /// JSON 报告输出路径
+    pub output: Option<PathBuf>,
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Command::Update(UpdateArgs::default())
+    }
+}
+
+impl Default for UpdateArgs {
+    fn default() -> Self {
+        Self {
+            release_dir: PathBuf::from(DEFAULT_RELEASE_DIR),
+        }
+    }
+}
+
+impl Default for VerifyArgs {
+    fn default() -> Self {
+        Self {
+            release_dir: PathBuf::from(DEFAULT_RELEASE_DIR),
+            cache_dir: None,
+            cache_max_age: DEFAULT_VERIFY_CACHE_MAX_AGE,
+            concurrency: DEFAULT_VERIFY_CONCURRENCY,
+            reset: false,
+            output: None,
+        }
+    }
+}
+
+impl Default for RematchArgs {
+    fn default() -> Self {
+        Self {
+            release_dir: PathBuf::from(DEFAULT_RELEASE_DIR),
+        }
+    }
+}
+
+impl Default for EvalArgs {
+    fn default() -> Self {
+        Self {
+            release_dir: PathBuf::from(DEFAULT_RELEASE_DIR),
+            sample: 200,
+            seed: 0,
+            statuses: vec![
+                ConfirmStatus::Human,
+                ConfirmStatus::Match,
+                ConfirmStatus::Model,
+            ],
+            cache_dir: PathBuf::from(".cache/eval"),
+            replay: false,
+            output: None,
+        }
+    }
+}
+
+impl Command {
+    /// 所有子命令（参数为默认值）
+    fn all() -> [Command; 4] {
+        [
+            Command::default(),
+            Command::Eval(EvalArgs::default()),
+            Command::Rematch(RematchArgs::default()),
+            Command::Verify(VerifyArgs::default()),
+        ]
+    }
+
+    /// 子命令名称
+    fn name(&self) -> &'static str {
+        match self {
+            Command::Update(_) => "update",
+            Command::Eval(_) => "eval",
+            Command::Rematch(_) => "rematch",
+            Command::Verify(_) => "verify",
+        }
+    }
+
+    /// 接受该参数的子命令名称（由各子命令的参数解析判断，解析出错也说明接受该参数）
+    fn accepting(flag: &str) -> Vec<&'static str> {
+        Self::all()
+            .into_iter()
+            .filter_map(|mut command| {
+                let accepted = !matches!(command.parse_flag(flag, || Ok(String::new())), Ok(false));
+                accepted.then(|| command.name())
+            })
+            .collect()
+    }
+
+    /// 解析子命令专用参数，当前子命令不接受该参数时返回 false
+    fn parse_flag(&mut self, flag: &str, value: impl FnMut() -> Result<String>) -> Result<bool> {
+        match self {
+            Command::Update(update) => update.parse_flag(flag, value),
+            Command::Eval(eval) => eval.parse_flag(flag, value),
+            Command::Rematch(rematch) => rematch.parse_flag(flag, value),
+            Command::Verify(verify) => verify.parse_flag(flag, value),
+        }
+    }
+}
+
+impl EvalArgs {
+    fn parse_flag(
+        &mut self,
+        flag: &str,
+        mut value: impl FnMut() -> Result<String>,
+    ) -> Result<bool> {
+        match flag {
+            "--release" => self.release_dir = PathBuf::from(value()?),
+            "--sample" => {
+                let sample = value()?;
+                self.sample = sample
+                    .parse()
+                    .with_context(|| format!("无效的 --sample: {}", sample))?;
+            }
+            "--seed" => {
+                let seed = value()?;
+                self.seed = seed
+                    .parse()
+                    .with_context(|| format!("无效的 --seed: {}", seed))?;
+            }
+            "--status" => {
+                self.statuses = value()?
+                    .split(',')
+                    .map(|s| s.trim().parse().map_err(anyhow::Error::msg))
+                    .collect::<Result<_>>()?;
+            }
+            "--cache" => self.cache_dir = PathBuf::from(value()?),
+            "--replay" => self.replay = true,
+            "--output" => self.output = Some(PathBuf::from(value()?)),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl UpdateArgs {
+    fn parse_flag(
+        &mut self,
+        flag: &str,
+        mut value: impl FnMut() -> Result<String>,
+    ) -> Result<bool> {
+        match flag {
+            "--release" => self.release_dir = PathBuf::from(value()?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl RematchArgs {
+    fn parse_flag(
+        &mut self,
+        flag: &str,
+        mut value: impl FnMut() -> Result<String>,
+    ) -> Result<bool> {
+        match flag {
+            "--release" => self.release_dir = PathBuf::from(value()?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl VerifyArgs {
+    fn parse_flag(
+        &mut self,
+        flag: &str,
+        mut value: impl FnMut() -> Result<String>,
+    ) -> Result<bool> {
+        match flag {
+            "--release" => self.release_dir = PathBuf::from(value()?),
+            "--cache" => self.cache_dir = Some(PathBuf::from(value()?)),
+            "--cache-max-age" => {
+                let hours = value()?;
+                let hours: u64 = hours
+                    .parse()
+                    .with_context(|| format!("无效的 --cache-max-age: {}", hours))?;
+                self.cache_max_age = Duration::from_secs(hours * 60 * 60);
+            }
+            "--concurrency" => {
+                let concurrency = value()?;
+                self.concurrency = concurrency
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .with_context(|| format!("无效的 --concurrency: {}", concurrency))?;
+            }
+            "--reset" => self.reset = true,
+            "--output" => self.output = Some(PathBuf::from(value()?)),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl Args {
+    pub fn parse() -> Result<Self> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter().peekable();
+
+        // 子命令参数直接解析到对应子命令的参数中
+        let subcommand = match args.peek().map(String::as_str) {
+            Some(name @ ("eval" | "rematch" | "verify")) => {
+                let name = name.to_string();
+                parsed.command = match name.as_str() {
+                    "eval" => Command::Eval(EvalArgs::default()),
+                    "rematch" => Command::Rematch(RematchArgs::default()),
+                    _ => Command::Verify(VerifyArgs::default()),
+                };
+                args.next();
+                Some(name)
+            }
+            _ => None,
+        };
+
+        while let Some(arg) = args.next() {
+            // 同时支持 `--flag value` 和 `--flag=value`
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .with_context(|| format!("{} 缺少参数值", flag))
+            };
+
+            match flag.as_str() {
+                "--llm-budget" => {
+                    let budget = value()?;
+                    parsed.llm_budget = Some(
+                        budget
+                            .parse()
+                            .with_context(|| format!("无效的 --llm-budget: {}", budget))?,
+                    );
+                }
+                "--llm-refresh" => parsed.llm_refresh = true,
+                "--carry-over" => parsed.carry_over = true,
+                "--recent" => parsed.recent = true,
+                "--enrich" => parsed.enrich = true,
+                "--calendar" => parsed.calendar = true,
+                "--seasons" => {
+                    let seasons = value()?;
+                    parsed.seasons = Some(
+                        seasons
+                            .parse()
+                            .map_err(anyhow::Error::msg)
+                            .with_context(|| format!("无效的 --seasons: {}", seasons))?,
+                    );
+                }
+                other => {
+                    if parsed.command.parse_flag(other, &mut value)? {
+                        continue;
+                    }
+                    let accepting = Command::accepting(other);
+                    if accepting.is_empty() {
+                        bail!("未知参数: {}", other);
+                    }
+                    match &subcommand {
+                        Some(name) => bail!("{} 不能用于 {} 子命令", other, name),
+                        None => bail!("{} 只能用于 {} 子命令", other, accepting.join("/")),
+                    }
+                }
+            }
+        }
+
+        if subcommand.is_some() {
+            if parsed.carry_over {
+                bail!("--carry-over 不能用于子命令");
+            }
+            if parsed.seasons.is_some() {
+                bail!("--seasons 不能用于子命令");
+            }
+            if parsed.recent {
+                bail!("--recent 不能用于子命令");
+            }
+            if parsed.enrich {
+                bail!("--enrich 不能用于子命令");
+            }
+            if parsed.calendar {
+                bail!("--calendar 不能用于子命令");
+            }
+        }
+        if parsed.recent && parsed.seasons.is_some() {
+            bail!("--recent 与 --seasons 不能同时使用");
+        }
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args> {
+        Args::parse_from(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_llm_budget() {
+        assert_eq!(parse(&[]).unwrap(), Args::default());
+        assert_eq!(
+            parse(&["--llm-budget", "2.5"]).unwrap().llm_budget,
+            Some(2.5)
+        );
+        assert_eq!(parse(&["--llm-budget=0.3"]).unwrap().llm_budget, Some(0.3));
+        assert!(parse(&["--llm-refresh"]).unwrap().llm_refresh);
+        assert!(parse(&["rematch", "--llm-refresh"]).unwrap().llm_refresh);
+        assert!(parse(&["--carry-over"]).unwrap().carry_over);
+        assert!(parse(&["eval", "--carry-over"]).is_err());
+
+        assert!(parse(&["--llm-budget"]).is_err());
+        assert!(parse(&["--llm-budget", "abc"]).is_err());
+        assert!(parse(&["--unknown"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_seasons() {
+        let args = parse(&["--seasons", "2025-fall..2026-winter"]).unwrap();
+        let seasons: Vec<_> = args.seasons.unwrap().map(|s| s.to_string()).collect();
+        assert_eq!(seasons, vec!["2025-fall", "2026-winter"]);
+        assert_eq!(
+            parse(&["--seasons=2024-spring"])
+                .unwrap()
+                .seasons
+                .unwrap()
+                .count(),
+            1
+        );
+
+        assert!(parse(&["--seasons", "2024-autumn"]).is_err());
+        assert!(parse(&["--seasons", "2026-winter..2025-fall"]).is_err());
+        assert!(parse(&["rematch", "--seasons", "2024-spring"]).is_err());
+
+        assert!(parse(&["--recent"]).unwrap().recent);
+        assert!(parse(&["--recent", "--seasons", "2024-spring"]).is_err());
+        assert!(parse(&["eval", "--recent"]).is_err());
+
+        assert!(parse(&["--recent", "--enrich"]).unwrap().enrich);
+        assert!(parse(&["rematch", "--enrich"]).is_err());
+        assert!(parse(&["--recent", "--calendar"]).unwrap().calendar);
+        assert!(parse(&["eval", "--calendar"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_update_release() {
+        assert_eq!(
+            parse(&["--recent", "--release", "data"]).unwrap().command,
+            Command::Update(UpdateArgs {
+                release_dir: PathBuf::from("data")
+            })
+        );
+        assert_eq!(
+            parse(&["--release=data"]).unwrap().command,
+            Command::Update(UpdateArgs {
+                release_dir: PathBuf::from("data")
+            })
+        );
+        assert!(parse(&["--release"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_eval() {
+        assert_eq!(
+            parse(&["eval"]).unwrap().command,
+            Command::Eval(EvalArgs::default())
+        );
+
+        let args = parse(&[
+            "eval",
+            "--sample=50",
+            "--seed",
+            "7",
+            "--status",
+            "human,model",
+            "--replay",
+            "--llm-budget",
+            "1",
+        ])
+        .unwrap();
+        assert_eq!(args.llm_budget, Some(1.0));
+        let Command::Eval(eval) = args.command else {
+            panic!("expected eval command");
+        };
+        assert_eq!(eval.sample, 50);
+        assert_eq!(eval.seed, 7);
+        assert_eq!(
+            eval.statuses,
+            vec![ConfirmStatus::Human, ConfirmStatus::Model]
+        );
+        assert!(eval.replay);
+
+        let Command::Eval(eval) = parse(&[
+            "eval",
+            "--release",
+            "data",
+            "--cache",
+            "dir",
+            "--output=a.json",
+        ])
+        .unwrap()
+        .command
+        else {
+            panic!("expected eval command");
+        };
+        assert_eq!(eval.release_dir, PathBuf::from("data"));
+        assert_eq!(eval.cache_dir, PathBuf::from("dir"));
+        assert_eq!(eval.output, Some(PathBuf::from("a.json")));
+
+        assert!(parse(&["eval", "--status", "confirmed"]).is_err());
+        // eval 参数不能用于 update
+        assert!(parse(&["--sample", "10"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_rematch() {
+        assert_eq!(
+            parse(&["rematch"]).unwrap().command,
+            Command::Rematch(RematchArgs::default())
+        );
+        assert_eq!(
+            parse(&["rematch", "--release", "data"]).unwrap().command,
+            Command::Rematch(RematchArgs {
+                release_dir: PathBuf::from("data")
+            })
+        );
+
+        assert!(parse(&["rematch", "--sample", "10"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_verify() {
+        assert_eq!(
+            parse(&["verify"]).unwrap().command,
+            Command::Verify(VerifyArgs::default())
+        );
+
+        let args = parse(&[
+            "verify",
+            "--release=data",
+            "--cache",
+            ".cache/verify",
+            "--cache-max-age",
+            "2",
+            "--concurrency",
+            "8",
+            "--reset",
+            "--output",
+            "verify.json",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.command,
+            Command::Verify(VerifyArgs {
+                release_dir: PathBuf::from("data"),
+                cache_dir: Some(PathBuf::from(".cache/verify")),
+                cache_max_age: Duration::from_secs(2 * 60 * 60),
+                concurrency: 8,
+                reset: true,
+                output: Some(PathBuf::from("verify.json")),
+            })
+        );
+
+        assert!(parse(&["verify", "--concurrency", "0"]).is_err());
+        assert!(parse(&["verify", "--cache-max-age", "1h"]).is_err());
+        assert!(parse(&["verify", "--sample", "10"]).is_err());
+        assert!(parse(&["verify", "--enrich"]).is_err());
+        // verify 参数不能用于其他命令
+        assert!(parse(&["eval", "--reset"]).is_err());
+        assert!(parse(&["rematch", "--cache", "dir"]).is_err());
+        assert!(parse(&["--reset"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_error_messages() {
+        let error = |args: &[&str]| parse(args).unwrap_err().to_string();
+        assert_eq!(error(&["--bogus"]), "未知参数: --bogus");
+        assert_eq!(
+            error(&["--cache", "dir"]),
+            "--cache 只能用于 eval/verify 子命令"
+        );
+        assert_eq!(
+            error(&["rematch", "--reset"]),
+            "--reset 不能用于 rematch 子命令"
+        );
+        assert_eq!(error(&["eval", "--bogus"]), "未知参数: --bogus");
+    }
+}
@@ -0,0 +1,362 @@
+use super::candidate_entry;
+use crate::services::bgmtv::{BgmtvClient, Subject};
+use crate::services::llm::{
+    CandidateEntry, LlmClient, LlmError, MalEntry, MatchDecision, Message, PromptContext,
+    PromptTemplate, Tool, ToolCall,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use thiserror::Error;
+use tracing::debug;
+
+/// 工具调用匹配的系统提示
+const AGENT_SYSTEM_PROMPT: &str = r#"为MAL条目找到对应的Bangumi条目。BGM下的候选已被排除(可能为空)。可用工具换关键词搜索(罗马音/英文/中文/去副标题/去季数)或查看条目详情。续作季数须一致,类型日期集数应符。最终只输出JSON:{"id":数字|null,"conf":"high|mid|low","reason":""},id必须出现在工具结果中"#;
+
+/// 默认最多请求轮数（每轮一次 LLM 请求）
+pub const DEFAULT_AGENT_MAX_TURNS: usize = 6;
+
+/// 条目详情中简介的最大字符数
+const SUMMARY_MAX_CHARS: usize = 200;
+
+const SEARCH_TOOL: &str = "search_bangumi";
+const SUBJECT_TOOL: &str = "get_subject";
+
+#[derive(Error, Debug)]
+pub enum AgentError {
+    #[error("LLM error: {0}")]
+    Llm(#[from] LlmError),
+    #[error("Agent exceeded {0} turns without a final answer")]
+    TurnLimit(usize),
+}
+
+/// 工具调用匹配：模型可以换关键词搜索 Bangumi、查看条目详情，再给出判断
+///
+/// 用于日期限制与不限日期的搜索都只返回无关候选的条目
+#[derive(Debug, Clone, Copy)]
+pub struct MatchAgent {
+    max_turns: usize,
+}
+
+impl Default for MatchAgent {
+    fn default() -> Self {
+        Self {
+            max_turns: DEFAULT_AGENT_MAX_TURNS,
+        }
+    }
+}
+
+/// 工具调用匹配的结果
+#[derive(Debug, Clone)]
+pub struct AgentMatch {
+    pub decision: MatchDecision,
+    /// 选中的条目（一定来自工具结果），无匹配为 None
+    pub subject: Option<Subject>,
+}
+
+impl MatchAgent {
+    pub fn new(max_turns: usize) -> Self {
+        Self {
+            max_turns: max_turns.max(1),
+        }
+    }
+
+    pub fn max_turns(&self) -> usize {
+        self.max_turns
+    }
+
+    /// 运行工具调用循环
+    ///
+    /// `rejected` 为已被排除的候选（写入提示，避免重复选择）。模型给出的 ID
+    /// 不在任何工具结果中时要求其重新回答，计入轮数
+    pub(crate) async fn resolve(
+        &self,
+        llm: &LlmClient,
+        bgm: &BgmtvClient,
+        mal: &MalEntry<'_>,
+        rejected: &[CandidateEntry<'_>],
+    ) -> Result<AgentMatch, AgentError> {
+        let mut messages = vec![
+            Message::system(AGENT_SYSTEM_PROMPT),
+            Message::user(llm.match_input(mal, rejected)),
+        ];
+        let mut seen: HashMap<u64, Subject> = HashMap::new();
+        let mut searches = Vec::new();
+
+        for _ in 0..self.max_turns {
+            let request = llm.tool_request(messages.clone(), tools());
+            let response = llm.chat(&request).await?;
+            let message = response.message().ok_or(LlmError::NoContent)?.clone();
+
+            if message.tool_calls.is_empty() {
+                let mut decision = llm.parse_decision(&response)?;
+                if let Some(id) = decision.id.filter(|id| !seen.contains_key(id)) {
+                    debug!(
+                        mal = mal.title,
+                        bgm_id = id,
+                        "agent 选中的 ID 未出现在工具结果中"
+                    );
+                    messages.push(message);
+                    messages.push(Message::user(format!(
+                        "id {} 未出现在工具结果中,请先搜索或查看该条目",
+                        id
+                    )));
+                    continue;
+                }
+
+                decision.searches = searches;
+                let subject = decision.id.and_then(|id| seen.remove(&id));
+                return Ok(AgentMatch { decision, subject });
+            }
+
+            messages.push(message.clone());
+            for call in &message.tool_calls {
+                let output = match ToolInvocation::parse(call) {
+                    Ok(invocation) => {
+                        if let ToolInvocation::Search { keyword } = &invocation {
+                            searches.push(keyword.clone());
+                        }
+                        invocation
+                            .run(bgm, llm.prompt_template(), &mal.studios, &mut seen)
+                            .await
+                    }
+                    Err(e) => e,
+                };
+                debug!(
+                    mal = mal.title,
+                    tool = %call.function.name,
+                    arguments = %call.function.arguments,
+                    output = %output,
+                    "agent tool call"
+                );
+                messages.push(Message::tool(&call.id, output));
+            }
+        }
+
+        Err(AgentError::TurnLimit(self.max_turns))
+    }
+}
+
+/// 提供给模型的工具定义
+fn tools() -> Vec<Tool> {
+    vec![
+        Tool::function(
+            SEARCH_TOOL,
+            "按关键词搜索Bangumi动画条目(不限日期)",
+            json!({
+                "type": "object",
+                "properties": {
+                    "keyword": { "type": "string" }
+                },
+                "required": ["keyword"]
+            }),
+        ),
+        Tool::function(
+            SUBJECT_TOOL,
+            "查看Bangumi条目详情(含简介)",
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer" }
+                },
+                "required": ["id"]
+            }),
+        ),
+    ]
+}
+
+/// 解析后的工具调用
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ToolInvocation {
+    Search { keyword: String },
+    Subject { id: u64 },
+}
+
+#[derive(Deserialize)]
+struct SearchArgs {
+    keyword: String,
+}
+
+#[derive(Deserialize)]
+struct SubjectArgs {
+    id: u64,
+}
+
+impl ToolInvocation {
+    /// 解析工具调用，失败时返回给模型的错误信息
+    fn parse(call: &ToolCall) -> Result<Self, String> {
+        let arguments = call.function.arguments.as_str();
+        let invalid = |e: serde_json::Error| format!("error: 参数无效 {} - {}", arguments, e);
+        match call.function.name.as_str() {
+            SEARCH_TOOL => {
+                let args: SearchArgs = serde_json::from_str(arguments).map_err(invalid)?;
+                let keyword = args.keyword.trim();
+                if keyword.is_empty() {
+                    return Err("error: keyword 不能为空".to_string());
+                }
+                Ok(ToolInvocation::Search {
+                    keyword: keyword.to_string(),
+                })
+            }
+            SUBJECT_TOOL => {
+                let args: SubjectArgs = serde_json::from_str(arguments).map_err(invalid)?;
+                Ok(ToolInvocation::Subject { id: args.id })
+            }
+            name => Err(format!("error: 未知工具 {}", name)),
+        }
+    }
+
+    /// 执行工具调用，结果中出现的条目按 `template` 的候选行格式化，并记入 `seen`
+    ///
+    /// `mal_studios` 用于标注条目的制作公司是否一致。Bangumi API 出错时把错误返回给模型，
+    /// 由模型决定是否重试或换关键词
+    async fn run(
+        &self,
+        bgm: &BgmtvClient,
+        template: &PromptTemplate,
+        mal_studios: &[&str],
+        seen: &mut HashMap<u64, Subject>,
+    ) -> String {
+        match self {
+            ToolInvocation::Search { keyword } => {
+                match bgm.search_anime_by_keyword_no_date(keyword).await {
+                    Ok(subjects) => {
+                        let output = format_search_results(template, &subjects, mal_studios);
+                        seen.extend(subjects.into_iter().map(|s| (s.id, s)));
+                        output
+                    }
+                    Err(e) => format!("error: {}", e),
+                }
+            }
+            ToolInvocation::Subject { id } => match bgm.get_subject(*id).await {
+                Ok(subject) => {
+                    let output = format_subject_detail(template, &subject, mal_studios);
+                    seen.insert(subject.id, subject);
+                    output
+                }
+                Err(e) => format!("error: {}", e),
+            },
+        }
+    }
+}
+
+/// 搜索结果，每行一个条目
+fn format_search_results(
+    template: &PromptTemplate,
+    subjects: &[Subject],
+    mal_studios: &[&str],
+) -> String {
+    if subjects.is_empty() {
+        return "无结果".to_string();
+    }
+    subjects
+        .iter()
+        .map(|s| template.candidate_line(&candidate_entry(s, mal_studios), PromptContext::Full))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 条目详情：候选行 + 截断的简介
+fn format_subject_detail(
+    template: &PromptTemplate,
+    subject: &Subject,
+    mal_studios: &[&str],
+) -> String {
+    let mut output =
+        template.candidate_line(&candidate_entry(subject, mal_studios), PromptContext::Full);
+    if let Some(summary) = subject.summary.as_deref().map(str::trim)
+        && !summary.is_empty()
+    {
+        output.push('\n');
+        output.extend(summary.chars().take(SUMMARY_MAX_CHARS));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::llm::FunctionCall;
+
+    fn call(name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: "call_0".to_string(),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: name.to_string(),
+                arguments: arguments.to_string(),
+            },
+        }
+    }
+
+    fn subject(json: &str) -> Subject {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_parse_tool_invocation() {
+        assert_eq!(
+            ToolInvocation::parse(&call(SEARCH_TOOL, r#"{"keyword":" Frieren "}"#)),
+            Ok(ToolInvocation::Search {
+                keyword: "Frieren".to_string()
+            })
+        );
+        assert_eq!(
+            ToolInvocation::parse(&call(SUBJECT_TOOL, r#"{"id":400602}"#)),
+            Ok(ToolInvocation::Subject { id: 400602 })
+        );
+
+        // 错误信息返回给模型，而不是中断循环
+        assert!(
+            ToolInvocation::parse(&call(SEARCH_TOOL, r#"{"keyword":""}"#))
+                .unwrap_err()
+                .starts_with("error:")
+        );
+        assert!(ToolInvocation::parse(&call(SUBJECT_TOOL, "{id:1}")).is_err());
+        assert!(ToolInvocation::parse(&call("delete", "{}")).is_err());
+    }
+
+    #[test]
+    fn test_tools_definition() {
+        let json = serde_json::to_value(tools()).unwrap();
+        assert_eq!(json[0]["type"], "function");
+        assert_eq!(json[0]["function"]["name"], SEARCH_TOOL);
+        assert_eq!(json[1]["function"]["name"], SUBJECT_TOOL);
+        assert_eq!(json[1]["function"]["parameters"]["required"], json!(["id"]));
+    }
+
+    #[test]
+    fn test_format_tool_output() {
+        let template = PromptTemplate::builtin();
+        assert_eq!(format_search_results(template, &[], &[]), "无结果");
+
+        let frieren = subject(
+            r#"{"id":400602,"type":2,"name":"葬送のフリーレン","name_cn":"葬送的芙莉莲",
+                "date":"2023-09-29","platform":"TV","eps":28,"summary":" 勇者一行打倒魔王后…… ",
+                "infobox":[{"key":"动画制作","value":"マッドハウス"}]}"#,
+        );
+        assert_eq!(
+            format_search_results(template, std::slice::from_ref(&frieren), &["Madhouse"]),
+            "400602:葬送のフリーレン|葬送的芙莉莲(TV,2023-09-29,28ep,マッドハウス,制作一致)"
+        );
+        assert_eq!(
+            format_subject_detail(template, &frieren, &[]),
+            "400602:葬送のフリーレン|葬送的芙莉莲(TV,2023-09-29,28ep,マッドハウス)\n勇者一行打倒魔王后……"
+        );
+
+        let long = subject(&format!(
+            r#"{{"id":1,"type":2,"name":"a","summary":"{}"}}"#,
+            "字".repeat(300)
+        ));
+        assert_eq!(
+            format_subject_detail(template, &long, &[]).chars().count(),
+            "1:a\n".chars().count() + SUMMARY_MAX_CHARS
+        );
+    }
+
+    #[test]
+    fn test_agent_max_turns() {
+        assert_eq!(MatchAgent::default().max_turns(), DEFAULT_AGENT_MAX_TURNS);
+        assert_eq!(MatchAgent::new(0).max_turns(), 1);
+    }
+}
@@ -0,0 +1,230 @@
+use super::YearSeason;
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 日期搜索的容差（向前后各扩展）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateTolerance {
+    Days(u32),
+    Months(u32),
+    Years(u32),
+}
+
+impl DateTolerance {
+    /// 默认逐级放宽：±2 周、±3 个月、±1 年
+    pub fn defaults() -> Vec<DateTolerance> {
+        vec![
+            DateTolerance::Days(14),
+            DateTolerance::Months(3),
+            DateTolerance::Years(1),
+        ]
+    }
+
+    fn sub_from(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match *self {
+            DateTolerance::Days(n) => date.checked_sub_days(Days::new(n.into())),
+            DateTolerance::Months(n) => date.checked_sub_months(Months::new(n)),
+            DateTolerance::Years(n) => date.checked_sub_months(Months::new(n * 12)),
+        }
+    }
+
+    fn add_to(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match *self {
+            DateTolerance::Days(n) => date.checked_add_days(Days::new(n.into())),
+            DateTolerance::Months(n) => date.checked_add_months(Months::new(n)),
+            DateTolerance::Years(n) => date.checked_add_months(Months::new(n * 12)),
+        }
+    }
+}
+
+impl std::str::FromStr for DateTolerance {
+    type Err = String;
+
+    /// `14d` / `2w` / `3m` / `1y`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || format!("无效的日期容差: {}（如 14d、2w、3m、1y）", s);
+        let unit = s.chars().last().ok_or_else(invalid)?;
+        let n: u32 = s[..s.len() - unit.len_utf8()]
+            .parse()
+            .map_err(|_| invalid())?;
+        match unit {
+            'd' => Ok(DateTolerance::Days(n)),
+            'w' => Ok(DateTolerance::Days(n * 7)),
+            'm' => Ok(DateTolerance::Months(n)),
+            'y' => Ok(DateTolerance::Years(n)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// 放送日期范围（闭区间），用于限制 Bangumi 搜索的 `air_date`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AirDateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl AirDateRange {
+    /// 由 MAL `start_date` 构建（MAL 可能只给出年月 `2024-04` 或年份 `2024`）
+    pub fn from_mal_date(date: &str) -> Option<Self> {
+        let parts: Vec<&str> = date.trim().split('-').collect();
+        let year: i32 = parts.first()?.parse().ok()?;
+        match parts[1..] {
+            [month, day] => {
+                let date = NaiveDate::from_ymd_opt(year, month.parse().ok()?, day.parse().ok()?)?;
+                Some(Self {
+                    start: date,
+                    end: date,
+                })
+            }
+            [month] => {
+                let start = NaiveDate::from_ymd_opt(year, month.parse().ok()?, 1)?;
+                let end = start.checked_add_months(Months::new(1))?.pred_opt()?;
+                Some(Self { start, end })
+            }
+            [] => Some(Self {
+                start: NaiveDate::from_ymd_opt(year, 1, 1)?,
+                end: NaiveDate::from_ymd_opt(year, 12, 31)?,
+            }),
+            _ => None,
+        }
+    }
+
+    /// 季度的固定范围（MAL 没有 `start_date` 时使用）
+    pub fn season(season: YearSeason) -> Self {
+        let (start, end) = season.air_window();
+        Self { start, end }
+    }
+
+    /// 向前后各扩展容差（超出日期范围时取边界）
+    pub fn widen(&self, tolerance: DateTolerance) -> Self {
+        Self {
+            start: tolerance.sub_from(self.start).unwrap_or(NaiveDate::MIN),
+            end: tolerance.add_to(self.end).unwrap_or(NaiveDate::MAX),
+        }
+    }
+
+    /// 日期（`YYYY-MM-DD`，只有年月或年份时取整月、整年）是否完全落在范围内
+    pub fn contains_date(&self, date: &str) -> bool {
+        Self::from_mal_date(date).is_some_and(|d| d.start >= self.start && d.end <= self.end)
+    }
+
+    /// 搜索参数 `(>=start, <end)`，结束日期包含在内
+    pub fn query_bounds(&self) -> (String, String) {
+        let end = self.end.succ_opt().unwrap_or(self.end);
+        (
+            self.start.format(DATE_FORMAT).to_string(),
+            end.format(DATE_FORMAT).to_string(),
+        )
+    }
+}
+
+impl std::fmt::Display for AirDateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else if self.start.year() == self.end.year() {
+            write!(f, "{}~{}", self.start, self.end.format("%m-%d"))
+        } else {
+            write!(f, "{}~{}", self.start, self.end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::mal::Season;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DATE_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn test_parse_tolerance() {
+        assert_eq!("14d".parse(), Ok(DateTolerance::Days(14)));
+        assert_eq!("2w".parse(), Ok(DateTolerance::Days(14)));
+        assert_eq!(" 3m".parse(), Ok(DateTolerance::Months(3)));
+        assert_eq!("1y".parse(), Ok(DateTolerance::Years(1)));
+
+        assert!("".parse::<DateTolerance>().is_err());
+        assert!("3".parse::<DateTolerance>().is_err());
+        assert!("m".parse::<DateTolerance>().is_err());
+        assert!("3h".parse::<DateTolerance>().is_err());
+    }
+
+    #[test]
+    fn test_from_mal_date() {
+        let range = AirDateRange::from_mal_date("2024-03-30").unwrap();
+        assert_eq!(
+            (range.start, range.end),
+            (date("2024-03-30"), date("2024-03-30"))
+        );
+
+        // 只有年月 / 年份
+        let range = AirDateRange::from_mal_date("2024-02").unwrap();
+        assert_eq!(
+            (range.start, range.end),
+            (date("2024-02-01"), date("2024-02-29"))
+        );
+        let range = AirDateRange::from_mal_date("2024").unwrap();
+        assert_eq!(
+            (range.start, range.end),
+            (date("2024-01-01"), date("2024-12-31"))
+        );
+
+        assert!(AirDateRange::from_mal_date("").is_none());
+        assert!(AirDateRange::from_mal_date("2024-13-01").is_none());
+        assert!(AirDateRange::from_mal_date("2024-01-01-01").is_none());
+    }
+
+    #[test]
+    fn test_contains_date() {
+        let window = AirDateRange::from_mal_date("2024-04-06")
+            .unwrap()
+            .widen(DateTolerance::Days(14));
+        assert!(window.contains_date("2024-04-06"));
+        assert!(window.contains_date("2024-03-23"));
+        assert!(!window.contains_date("2023-04-08"));
+        // 只有年份时无法确定在范围内
+        assert!(!window.contains_date("2024"));
+        assert!(!window.contains_date(""));
+    }
+
+    #[test]
+    fn test_widen() {
+        // 3 月 30 日开播，Bangumi 登记为 4 月初也能搜到
+        let range = AirDateRange::from_mal_date("2024-03-30").unwrap();
+        let widened = range.widen(DateTolerance::Days(14));
+        assert_eq!(
+            widened.query_bounds(),
+            ("2024-03-16".to_string(), "2024-04-14".to_string())
+        );
+        assert_eq!(widened.to_string(), "2024-03-16~04-13");
+
+        // 月末按月份长度截断
+        let range = AirDateRange::from_mal_date("2024-05-31").unwrap();
+        let widened = range.widen(DateTolerance::Months(3));
+        assert_eq!(
+            (widened.start, widened.end),
+            (date("2024-02-29"), date("2024-08-31"))
+        );
+        let widened = range.widen(DateTolerance::Years(1));
+        assert_eq!(widened.to_string(), "2023-05-31~2025-05-31");
+    }
+
+    #[test]
+    fn test_season_range() {
+        let range = AirDateRange::season(YearSeason::new(2026, Season::Winter));
+        assert_eq!(
+            (range.start, range.end),
+            (date("2025-12-01"), date("2026-03-31"))
+        );
+        assert_eq!(
+            range.query_bounds(),
+            ("2025-12-01".to_string(), "2026-04-01".to_string())
+        );
+    }
+}
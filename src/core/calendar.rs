@@ -0,0 +1,317 @@
+use super::title_match::names_similarity;
+use super::{
+    BgmCandidate, ConfirmStatus, CoreError, MediaType, SeasonData, SeasonProcessor, YearSeason,
+    save_json,
+};
+use crate::services::bgmtv::{CalendarDay, CalendarSubject};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use tracing::info;
+
+/// 每日放送条目加入未确认条目候选的最低标题相似度
+const CALENDAR_CANDIDATE_MIN_SCORE: f64 = 0.5;
+
+/// 没有被任何条目引用的每日放送条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarEntry {
+    pub bgm_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_cn: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub air_date: Option<String>,
+    /// 1 ~ 7（星期一 ~ 星期日）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub air_weekday: Option<u8>,
+}
+
+impl From<&CalendarSubject> for CalendarEntry {
+    fn from(s: &CalendarSubject) -> Self {
+        let non_empty = |value: &Option<String>| value.clone().filter(|v| !v.is_empty());
+        Self {
+            bgm_id: s.id,
+            name: non_empty(&s.name),
+            name_cn: non_empty(&s.name_cn),
+            air_date: non_empty(&s.air_date),
+            air_weekday: s.air_weekday,
+        }
+    }
+}
+
+/// 映射的 Bangumi 条目不在每日放送中的 TV 条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissingFromCalendar {
+    pub mal_id: u64,
+    pub mal_title: String,
+    pub bgm_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bgm_name: Option<String>,
+    pub status: ConfirmStatus,
+}
+
+/// 当前季度与 Bangumi 每日放送的对照（`{year}-{season}-calendar.json`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarReport {
+    pub season: YearSeason,
+    pub update_time: String,
+    /// 每日放送中的条目数
+    pub calendar_total: usize,
+    /// 没有被任何条目（包括续播条目与未确认条目的预选）引用的每日放送条目
+    pub unclaimed: Vec<CalendarEntry>,
+    /// 已确认映射的 TV 条目，映射的 Bangumi 条目不在每日放送中
+    pub missing: Vec<MissingFromCalendar>,
+    /// 加入未确认条目候选的每日放送条目数
+    pub proposed: usize,
+}
+
+impl CalendarReport {
+    /// 对照季度数据与每日放送
+    pub fn new(data: &SeasonData, calendar: &[CalendarDay], proposed: usize) -> Self {
+        let calendar_ids: HashSet<u64> = calendar_subjects(calendar).map(|s| s.id).collect();
+        let claimed = claimed_bgm_ids(data);
+
+        let unclaimed = calendar_subjects(calendar)
+            .filter(|s| !claimed.contains(&s.id))
+            .map(CalendarEntry::from)
+            .collect();
+        let missing = data
+            .items
+            .iter()
+            .filter(|i| i.status.is_confirmed() && i.mal.media_type == MediaType::Tv)
+            .filter_map(|i| {
+                let bgm_id = i.bgm_id.filter(|id| !calendar_ids.contains(id))?;
+                Some(MissingFromCalendar {
+                    mal_id: i.mal.id,
+                    mal_title: i.mal.title.clone(),
+                    bgm_id,
+                    bgm_name: i.bgm_name.clone(),
+                    status: i.status,
+                })
+            })
+            .collect();
+
+        Self {
+            season: data.season,
+            update_time: SeasonData::new(data.season).update_time,
+            calendar_total: calendar_ids.len(),
+            unclaimed,
+            missing,
+            proposed,
+        }
+    }
+
+    /// 保存到文件
+    pub async fn save(&self, path: &Path) -> Result<(), CoreError> {
+        save_json(self, path).await
+    }
+}
+
+/// 每日放送中的所有条目（按 ID 去重）
+fn calendar_subjects(calendar: &[CalendarDay]) -> impl Iterator<Item = &CalendarSubject> {
+    let mut seen = HashSet::new();
+    calendar
+        .iter()
+        .flat_map(|day| &day.items)
+        .filter(move |s| seen.insert(s.id))
+}
+
+/// 季度数据中引用的 Bangumi 条目（条目与续播条目的 `bgm_id`）
+fn claimed_bgm_ids(data: &SeasonData) -> HashSet<u64> {
+    data.items
+        .iter()
+        .filter_map(|i| i.bgm_id)
+        .chain(data.carry_over.iter().filter_map(|c| c.bgm_id))
+        .collect()
+}
+
+/// 反向对照：没有被引用的每日放送条目按标题相似度加入未确认条目的候选，返回加入的数量
+fn propose_calendar_candidates(data: &mut SeasonData, calendar: &[CalendarDay]) -> usize {
+    let claimed = claimed_bgm_ids(data);
+    let unclaimed: Vec<&CalendarSubject> = calendar_subjects(calendar)
+        .filter(|s| !claimed.contains(&s.id))
+        .collect();
+
+    let mut proposed = 0;
+    for item in data
+        .items
+        .iter_mut()
+        .filter(|i| i.status == ConfirmStatus::Unconfirmed)
+    {
+        let mal_titles = item.mal.titles();
+        for subject in &unclaimed {
+            if item.candidates.iter().any(|c| c.bgm_id == subject.id) {
+                continue;
+            }
+            let names: Vec<&str> = [&subject.name, &subject.name_cn]
+                .into_iter()
+                .filter_map(|name| name.as_deref())
+                .collect();
+            let score = names_similarity(&mal_titles, &names);
+            if score < CALENDAR_CANDIDATE_MIN_SCORE {
+                continue;
+            }
+            let entry = CalendarEntry::from(*subject);
+            item.candidates.push(BgmCandidate {
+                bgm_id: entry.bgm_id,
+                bgm_name: entry.name.unwrap_or_default(),
+                bgm_name_cn: entry.name_cn,
+                date: entry.air_date,
+                thumbnail: subject
+                    .images
+                    .as_ref()
+                    .and_then(|i| i.small.clone())
+                    .filter(|s| !s.is_empty()),
+                score: Some(score),
+                ..Default::default()
+            });
+            proposed += 1;
+        }
+    }
+    proposed
+}
+
+impl SeasonProcessor {
+    /// 与 Bangumi 每日放送对照（只对当前放送中的季度有意义）
+    ///
+    /// 没有被引用的每日放送条目按标题相似度加入未确认条目的候选（修改 `data`，由调用方保存）
+    pub async fn cross_check_calendar(
+        &self,
+        data: &mut SeasonData,
+    ) -> Result<CalendarReport, CoreError> {
+        let calendar = self.bgm_client.get_calendar().await?;
+        let proposed = propose_calendar_candidates(data, &calendar);
+        let report = CalendarReport::new(data, &calendar, proposed);
+        info!(
+            season = %data.season,
+            calendar_total = report.calendar_total,
+            unclaimed = report.unclaimed.len(),
+            missing = report.missing.len(),
+            proposed = proposed,
+            "每日放送对照完成"
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{CarryOverItem, MalInfo, SeasonItem, test_util};
+    use crate::services::mal::Season;
+
+    fn mal(id: u64, title_ja: &str, media_type: MediaType) -> MalInfo {
+        MalInfo {
+            title_ja: Some(title_ja.to_string()),
+            media_type,
+            ..test_util::mal(id)
+        }
+    }
+
+    fn calendar() -> Vec<CalendarDay> {
+        serde_json::from_value(serde_json::json!([
+            {
+                "weekday": { "id": 5 },
+                "items": [
+                    { "id": 515759, "name": "葬送のフリーレン 第2期", "name_cn": "葬送的芙莉莲 第二季", "air_weekday": 5 },
+                    { "id": 600001, "name": "薬屋のひとりごと 第3期", "name_cn": "", "air_weekday": 5 },
+                    { "id": 600002, "name": "続播アニメ", "air_weekday": 5 }
+                ]
+            },
+            {
+                "weekday": { "id": 6 },
+                "items": [{ "id": 600003, "name": "無関係のアニメ", "air_weekday": 6 }]
+            }
+        ]))
+        .unwrap()
+    }
+
+    fn season_data() -> SeasonData {
+        let mut data = SeasonData::new(YearSeason::new(2026, Season::Winter));
+        data.items = vec![
+            SeasonItem {
+                bgm_id: Some(515759),
+                ..SeasonItem::new(
+                    ConfirmStatus::Match,
+                    mal(1, "葬送のフリーレン 第2期", MediaType::Tv),
+                )
+            },
+            // 映射的条目不在每日放送中
+            SeasonItem {
+                bgm_id: Some(700000),
+                bgm_name: Some("TV アニメ".to_string()),
+                ..SeasonItem::new(ConfirmStatus::Human, mal(2, "TV アニメ", MediaType::Tv))
+            },
+            // 剧场版本来就不在每日放送中
+            SeasonItem {
+                bgm_id: Some(700001),
+                ..SeasonItem::new(ConfirmStatus::Human, mal(3, "劇場版", MediaType::Movie))
+            },
+            SeasonItem::new(
+                ConfirmStatus::Unconfirmed,
+                mal(4, "薬屋のひとりごと 第三期", MediaType::Tv),
+            ),
+        ];
+        data.carry_over.push(CarryOverItem {
+            source: Some(YearSeason::new(2025, Season::Fall)),
+            status: ConfirmStatus::Human,
+            bgm_id: Some(600002),
+            bgm_name: None,
+            bgm_name_cn: None,
+            mal: mal(5, "続播アニメ", MediaType::Tv),
+        });
+        data
+    }
+
+    #[test]
+    fn test_calendar_report() {
+        let data = season_data();
+        let report = CalendarReport::new(&data, &calendar(), 0);
+
+        assert_eq!(report.calendar_total, 4);
+        let unclaimed: Vec<_> = report.unclaimed.iter().map(|e| e.bgm_id).collect();
+        assert_eq!(unclaimed, vec![600001, 600003]);
+        assert_eq!(report.unclaimed[0].name_cn, None);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].mal_id, 2);
+        assert_eq!(report.missing[0].bgm_id, 700000);
+    }
+
+    #[test]
+    fn test_propose_calendar_candidates() {
+        let mut data = season_data();
+        let proposed = propose_calendar_candidates(&mut data, &calendar());
+        assert_eq!(proposed, 1);
+
+        let item = &data.items[3];
+        assert_eq!(item.candidates.len(), 1);
+        assert_eq!(item.candidates[0].bgm_id, 600001);
+        assert!(item.candidates[0].score.unwrap() >= CALENDAR_CANDIDATE_MIN_SCORE);
+        // 只加入候选，不预选
+        assert_eq!(item.bgm_id, None);
+
+        // 已经是候选的不重复加入
+        assert_eq!(propose_calendar_candidates(&mut data, &calendar()), 0);
+
+        // 候选不算引用，报告中仍列出
+        let report = CalendarReport::new(&data, &calendar(), proposed);
+        assert_eq!(report.unclaimed.len(), 2);
+        assert_eq!(report.proposed, 1);
+    }
+
+    #[test]
+    fn test_propose_by_synonym() {
+        let mut data = SeasonData::new(YearSeason::new(2026, Season::Winter));
+        data.items = vec![SeasonItem::new(
+            ConfirmStatus::Unconfirmed,
+            MalInfo {
+                synonyms: vec!["無関係のアニメ".to_string()],
+                ..mal(6, "別のタイトル", MediaType::Tv)
+            },
+        )];
+
+        assert_eq!(propose_calendar_candidates(&mut data, &calendar()), 1);
+        assert_eq!(data.items[0].candidates[0].bgm_id, 600003);
+    }
+}
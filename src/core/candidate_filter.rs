@@ -0,0 +1,128 @@
+use super::MediaType;
+use crate::services::bgmtv::{Subject, SubjectType};
+use serde::{Deserialize, Serialize};
+
+/// 候选被过滤的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterReason {
+    /// 条目类型不是动画
+    NotAnime,
+    /// 放送平台与 MAL 类型不符（如 MAL 剧场版对应 Bangumi TV 条目）
+    Platform,
+}
+
+/// MAL 类型对应的 Bangumi 放送平台（`platform`），为空时不限制
+///
+/// MAL 的 TV 与 ONA 在 Bangumi 上常互标为 TV / WEB，特别篇的平台不固定
+fn expected_platforms(media_type: MediaType) -> &'static [&'static str] {
+    match media_type {
+        MediaType::Tv | MediaType::Ona => &["TV", "WEB"],
+        MediaType::Ova => &["OVA"],
+        MediaType::Movie => &["剧场版"],
+        MediaType::TvSpecial => &["TV", "其他"],
+        MediaType::Special | MediaType::Music | MediaType::Pv => &[],
+    }
+}
+
+/// 搜索结果是否应被过滤（平台为空时无法判断，保留）
+fn filter_reason(media_type: MediaType, subject: &Subject) -> Option<FilterReason> {
+    if subject.subject_type != u8::from(SubjectType::Anime) {
+        return Some(FilterReason::NotAnime);
+    }
+    let platforms = expected_platforms(media_type);
+    let platform = subject.platform.as_deref().map(str::trim).unwrap_or("");
+    if platform.is_empty()
+        || platforms.is_empty()
+        || platforms.iter().any(|p| p.eq_ignore_ascii_case(platform))
+    {
+        return None;
+    }
+    Some(FilterReason::Platform)
+}
+
+/// 按条目类型与放送平台过滤搜索结果，系列条目（`series`）排在后面（同组内保持原顺序）
+///
+/// 返回保留的结果与被过滤的结果及原因
+pub(super) fn filter_candidates(
+    media_type: MediaType,
+    subjects: Vec<Subject>,
+) -> (Vec<Subject>, Vec<(Subject, FilterReason)>) {
+    let mut kept = Vec::new();
+    let mut filtered = Vec::new();
+    for subject in subjects {
+        match filter_reason(media_type, &subject) {
+            Some(reason) => filtered.push((subject, reason)),
+            None => kept.push(subject),
+        }
+    }
+    kept.sort_by_key(|s| s.series == Some(true));
+    (kept, filtered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subject(id: u64, subject_type: u8, platform: &str, series: bool) -> Subject {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "type": subject_type,
+            "platform": platform,
+            "series": series
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_filter_reason() {
+        let tv = subject(1, 2, "TV", false);
+        let movie = subject(2, 2, "剧场版", false);
+        let web = subject(3, 2, "WEB", false);
+        assert_eq!(filter_reason(MediaType::Tv, &tv), None);
+        assert_eq!(filter_reason(MediaType::Tv, &web), None);
+        assert_eq!(
+            filter_reason(MediaType::Movie, &tv),
+            Some(FilterReason::Platform)
+        );
+        assert_eq!(filter_reason(MediaType::Movie, &movie), None);
+        assert_eq!(
+            filter_reason(MediaType::Ova, &tv),
+            Some(FilterReason::Platform)
+        );
+        assert_eq!(
+            filter_reason(MediaType::Ona, &subject(4, 2, "web", false)),
+            None
+        );
+        // 特别篇不限制平台，平台为空时无法判断
+        assert_eq!(filter_reason(MediaType::Special, &movie), None);
+        assert_eq!(
+            filter_reason(MediaType::Movie, &subject(5, 2, "", false)),
+            None
+        );
+        assert_eq!(
+            filter_reason(MediaType::Tv, &subject(6, 1, "漫画", false)),
+            Some(FilterReason::NotAnime)
+        );
+    }
+
+    #[test]
+    fn test_filter_candidates() {
+        let subjects = vec![
+            subject(1, 2, "TV", true),
+            subject(2, 2, "剧场版", false),
+            subject(3, 2, "TV", false),
+            subject(4, 2, "WEB", false),
+        ];
+        let (kept, filtered) = filter_candidates(MediaType::Tv, subjects);
+        let ids: Vec<_> = kept.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![3, 4, 1]);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].0.id, 2);
+        assert_eq!(filtered[0].1, FilterReason::Platform);
+        assert_eq!(
+            serde_json::to_value(filtered[0].1).unwrap(),
+            serde_json::json!("platform")
+        );
+    }
+}
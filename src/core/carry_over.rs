@@ -0,0 +1,174 @@
+use super::{BgmCandidate, ConfirmStatus, CoreError, MalInfo, SeasonData, SeasonItem, YearSeason};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 续播条目：引用首次匹配所在季度的结果，不重新搜索 Bangumi
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarryOverItem {
+    /// 首次匹配所在的季度（如 `2025-fall`），之前的季度都没有收录时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<YearSeason>,
+    /// 首次匹配时的确认状态（没有引用时为 `unconfirmed`）
+    pub status: ConfirmStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bgm_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bgm_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bgm_name_cn: Option<String>,
+    pub mal: MalInfo,
+}
+
+impl CarryOverItem {
+    /// 没有找到首次匹配的续播条目
+    fn unmapped(mal: MalInfo) -> Self {
+        Self {
+            source: None,
+            status: ConfirmStatus::Unconfirmed,
+            bgm_id: None,
+            bgm_name: None,
+            bgm_name_cn: None,
+            mal,
+        }
+    }
+
+    /// 引用季度数据中的条目（MAL 信息使用最新的）
+    fn from_item(source: YearSeason, item: &SeasonItem, mal: MalInfo) -> Self {
+        Self {
+            source: Some(source),
+            status: item.status,
+            bgm_id: item.bgm_id,
+            bgm_name: item.bgm_name.clone(),
+            bgm_name_cn: item.bgm_name_cn.clone(),
+            mal,
+        }
+    }
+
+    /// 已引用的匹配结果
+    pub fn candidate(&self) -> Option<BgmCandidate> {
+        Some(BgmCandidate {
+            bgm_id: self.bgm_id?,
+            bgm_name: self.bgm_name.clone()?,
+            bgm_name_cn: self.bgm_name_cn.clone(),
+            ..Default::default()
+        })
+    }
+}
+
+/// 按需读取之前季度的数据（同一次处理中的续播条目共用）
+pub(crate) struct SeasonArchive {
+    release_dir: PathBuf,
+    loaded: HashMap<YearSeason, Option<SeasonData>>,
+}
+
+impl SeasonArchive {
+    pub(crate) fn new(release_dir: &Path) -> Self {
+        Self {
+            release_dir: release_dir.to_path_buf(),
+            loaded: HashMap::new(),
+        }
+    }
+
+    async fn get(&mut self, season: YearSeason) -> Result<Option<&SeasonData>, CoreError> {
+        if !self.loaded.contains_key(&season) {
+            let data = SeasonData::load(&season.release_file(&self.release_dir)).await?;
+            self.loaded.insert(season, data);
+        }
+        Ok(self.loaded[&season].as_ref())
+    }
+
+    /// 查找续播条目首次匹配的结果
+    ///
+    /// 先查开播季度（已知时）的条目，再查上一季度的条目与续播条目（沿用其引用）
+    pub(crate) async fn resolve(
+        &mut self,
+        mal: MalInfo,
+        start: Option<YearSeason>,
+        current: YearSeason,
+    ) -> Result<CarryOverItem, CoreError> {
+        if let Some(start) = start.filter(|&start| start < current)
+            && let Some(data) = self.get(start).await?
+            && let Some(item) = data.items.iter().find(|i| i.mal.id == mal.id)
+        {
+            return Ok(CarryOverItem::from_item(data.season, item, mal));
+        }
+
+        let Some(previous) = current.prev() else {
+            return Ok(CarryOverItem::unmapped(mal));
+        };
+        if let Some(data) = self.get(previous).await? {
+            if let Some(item) = data.items.iter().find(|i| i.mal.id == mal.id) {
+                return Ok(CarryOverItem::from_item(data.season, item, mal));
+            }
+            if let Some(previous) = data.carry_over.iter().find(|c| c.mal.id == mal.id) {
+                return Ok(CarryOverItem {
+                    mal,
+                    ..previous.clone()
+                });
+            }
+        }
+
+        Ok(CarryOverItem::unmapped(mal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_util::mal;
+    use crate::services::mal::Season;
+
+    #[tokio::test]
+    async fn test_resolve_carry_over() {
+        let dir =
+            std::env::temp_dir().join(format!("season-data-carry-over-{}", std::process::id()));
+
+        // 2025 秋季开播并匹配，2026 冬季续播
+        let fall_2025 = YearSeason::new(2025, Season::Fall);
+        let winter = YearSeason::new(2026, Season::Winter);
+        let mut fall = SeasonData::new(fall_2025);
+        fall.items.push(SeasonItem::matched(
+            ConfirmStatus::Human,
+            BgmCandidate {
+                bgm_id: 100,
+                bgm_name: "作品".to_string(),
+                bgm_name_cn: None,
+                ..Default::default()
+            },
+            mal(1),
+        ));
+        fall.save(&fall_2025.release_file(&dir)).await.unwrap();
+
+        let mut archive = SeasonArchive::new(&dir);
+        let item = archive
+            .resolve(mal(1), Some(fall_2025), winter)
+            .await
+            .unwrap();
+        assert_eq!(item.source, Some(fall_2025));
+        assert_eq!(item.bgm_id, Some(100));
+        assert_eq!(item.status, ConfirmStatus::Human);
+
+        // 开播季度未知时查上一季度
+        let item = archive.resolve(mal(1), None, winter).await.unwrap();
+        assert_eq!(item.source, Some(fall_2025));
+
+        // 2026 春季：上一季度（冬季）的续播条目沿用原引用
+        let mut winter_data = SeasonData::new(winter);
+        winter_data.carry_over.push(item);
+        winter_data.save(&winter.release_file(&dir)).await.unwrap();
+        let item = archive
+            .resolve(mal(1), None, YearSeason::new(2026, Season::Spring))
+            .await
+            .unwrap();
+        assert_eq!(item.source, Some(fall_2025));
+        assert_eq!(item.candidate().map(|c| c.bgm_id), Some(100));
+
+        // 都没有收录
+        let item = archive.resolve(mal(2), None, winter).await.unwrap();
+        assert!(item.source.is_none());
+        assert_eq!(item.status, ConfirmStatus::Unconfirmed);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
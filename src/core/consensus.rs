@@ -0,0 +1,163 @@
+use super::rng::shuffle;
+use crate::services::llm::{CandidateEntry, LlmClient, LlmError, MalEntry, MatchDecision};
+
+/// 双判定共识模式：第一次判断确认匹配后，再独立判断一次，两次一致才确认为 `model`
+pub enum Consensus {
+    /// 由另一个模型再判断一次（候选顺序不变）
+    SecondModel(Box<LlmClient>),
+    /// 同一模型，候选打乱顺序后再判断一次（抵消位置偏好）
+    ShuffledCandidates,
+}
+
+/// 打乱候选顺序：以候选 ID 为种子（同一组候选结果固定，可复用缓存），保证与原顺序不同
+fn shuffled<'a>(candidates: &[CandidateEntry<'a>]) -> Vec<CandidateEntry<'a>> {
+    let seed = candidates
+        .iter()
+        .fold(0u64, |seed, c| seed.rotate_left(7) ^ c.id);
+    let mut shuffled = candidates.to_vec();
+    shuffle(&mut shuffled, seed);
+    if shuffled.iter().zip(candidates).all(|(a, b)| a.id == b.id) {
+        shuffled.rotate_left(1);
+    }
+    shuffled
+}
+
+impl Consensus {
+    /// 是否需要第二次判断：打乱顺序至少需要 2 个候选，否则两次提示完全相同
+    pub(crate) fn applies(&self, candidates: &[CandidateEntry<'_>]) -> bool {
+        match self {
+            Consensus::SecondModel(_) => true,
+            Consensus::ShuffledCandidates => candidates.len() >= 2,
+        }
+    }
+
+    /// 获取第二次判断
+    pub(crate) async fn second_opinion(
+        &self,
+        llm: &LlmClient,
+        mal: &MalEntry<'_>,
+        candidates: &[CandidateEntry<'_>],
+    ) -> Result<MatchDecision, LlmError> {
+        match self {
+            Consensus::SecondModel(second) => second.match_anime(mal, candidates).await,
+            Consensus::ShuffledCandidates => llm.match_anime(mal, &shuffled(candidates)).await,
+        }
+    }
+
+    /// 第二个模型的客户端（用于汇总用量）
+    pub fn second_client(&self) -> Option<&LlmClient> {
+        match self {
+            Consensus::SecondModel(second) => Some(second),
+            Consensus::ShuffledCandidates => None,
+        }
+    }
+}
+
+/// 两次判断是否一致（选中同一条目，且第二次不是低置信度）
+pub(crate) fn agrees(first: &MatchDecision, second: &MatchDecision) -> bool {
+    first.id.is_some() && first.id == second.id && !second.is_low_confidence()
+}
+
+/// 共识统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConsensusStats {
+    /// 一致，确认为 model
+    pub agreed: u32,
+    /// 不一致，保持未确认
+    pub disagreed: u32,
+    /// 第二次判断失败（API 错误或预算用完），保持未确认
+    pub failed: u32,
+}
+
+impl ConsensusStats {
+    pub fn add(&mut self, other: &ConsensusStats) {
+        self.agreed += other.agreed;
+        self.disagreed += other.disagreed;
+        self.failed += other.failed;
+    }
+
+    /// 一致率（不含失败）
+    pub fn agreement_rate(&self) -> Option<f64> {
+        let total = self.agreed + self.disagreed;
+        (total > 0).then(|| self.agreed as f64 / total as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::llm::{Confidence, LlmJudge};
+
+    fn decision(id: Option<u64>, confidence: Confidence) -> MatchDecision {
+        MatchDecision {
+            id,
+            confidence: Some(confidence),
+            reason: None,
+            searches: vec![],
+            judge: LlmJudge {
+                provider: "deepseek".to_string(),
+                model: "deepseek-chat".to_string(),
+                prompt_version: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_agrees() {
+        let first = decision(Some(1), Confidence::High);
+        assert!(agrees(&first, &decision(Some(1), Confidence::High)));
+        assert!(agrees(&first, &decision(Some(1), Confidence::Medium)));
+        assert!(!agrees(&first, &decision(Some(1), Confidence::Low)));
+        assert!(!agrees(&first, &decision(Some(2), Confidence::High)));
+        assert!(!agrees(&first, &decision(None, Confidence::High)));
+
+        // 两次都无匹配不算确认
+        let none = decision(None, Confidence::High);
+        assert!(!agrees(&none, &none));
+    }
+
+    #[test]
+    fn test_shuffled() {
+        let entry = |id| CandidateEntry {
+            id,
+            name: "anime",
+            ..Default::default()
+        };
+        let ids = |entries: &[CandidateEntry]| entries.iter().map(|c| c.id).collect::<Vec<_>>();
+
+        for n in 2..8 {
+            let candidates: Vec<_> = (1..=n).map(entry).collect();
+            let first = shuffled(&candidates);
+            assert_ne!(ids(&first), ids(&candidates));
+            // 同一组候选顺序固定
+            assert_eq!(ids(&first), ids(&shuffled(&candidates)));
+            let mut sorted = ids(&first);
+            sorted.sort();
+            assert_eq!(sorted, ids(&candidates));
+        }
+
+        let consensus = Consensus::ShuffledCandidates;
+        assert!(!consensus.applies(&[entry(1)]));
+        assert!(consensus.applies(&[entry(1), entry(2)]));
+    }
+
+    #[test]
+    fn test_consensus_stats() {
+        let mut stats = ConsensusStats::default();
+        assert!(stats.agreement_rate().is_none());
+
+        stats.add(&ConsensusStats {
+            agreed: 3,
+            disagreed: 1,
+            failed: 2,
+        });
+        stats.add(&ConsensusStats {
+            agreed: 3,
+            disagreed: 1,
+            failed: 0,
+        });
+        assert_eq!(stats.agreed, 6);
+        assert_eq!(stats.failed, 2);
+        assert!((stats.agreement_rate().unwrap() - 0.75).abs() < 1e-9);
+    }
+}
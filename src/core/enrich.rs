@@ -0,0 +1,198 @@
+use super::{ConfirmStatus, CoreError, SeasonData, SeasonProcessor, YearSeason, save_json};
+use crate::services::bgmtv::Subject;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// 补充了 Bangumi 条目详情的已确认映射
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnrichedItem {
+    pub mal_id: u64,
+    pub bgm_id: u64,
+    pub status: ConfirmStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_cn: Option<String>,
+    /// 放送开始日期
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// 话数（`eps`，没有时为 `total_episodes`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<u32>,
+    /// 动画制作（信息框）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub studios: Vec<String>,
+}
+
+impl EnrichedItem {
+    fn new(mal_id: u64, status: ConfirmStatus, subject: &Subject) -> Self {
+        let rating = subject.rating.as_ref();
+        let infobox = subject.infobox_fields();
+        Self {
+            mal_id,
+            bgm_id: subject.id,
+            status,
+            name: subject.name.clone().filter(|s| !s.is_empty()),
+            name_cn: subject.name_cn.clone().filter(|s| !s.is_empty()),
+            date: subject
+                .date
+                .clone()
+                .filter(|s| !s.is_empty())
+                .or(infobox.air_date),
+            eps: subject
+                .eps
+                .filter(|&eps| eps > 0)
+                .or(subject.total_episodes.filter(|&eps| eps > 0))
+                .or(infobox.episodes),
+            platform: subject.platform.clone().filter(|s| !s.is_empty()),
+            // 评分人数不足时 Bangumi 返回 0 分、0 名
+            score: rating.and_then(|r| r.score).filter(|&score| score > 0.0),
+            rank: rating.and_then(|r| r.rank).filter(|&rank| rank > 0),
+            studios: infobox.studios,
+        }
+    }
+}
+
+/// 季度数据的补充输出（`{year}-{season}-enriched.json`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnrichedSeason {
+    pub season: YearSeason,
+    pub update_time: String,
+    pub items: Vec<EnrichedItem>,
+}
+
+impl EnrichedSeason {
+    /// 保存到文件
+    pub async fn save(&self, path: &Path) -> Result<(), CoreError> {
+        save_json(self, path).await
+    }
+}
+
+/// 需要补充详情的条目：已确认且有 `bgm_id`（`(mal_id, bgm_id, status)`）
+fn confirmed_mappings(data: &SeasonData) -> Vec<(u64, u64, ConfirmStatus)> {
+    data.items
+        .iter()
+        .filter(|item| item.status.is_confirmed())
+        .filter_map(|item| Some((item.mal.id, item.bgm_id?, item.status)))
+        .collect()
+}
+
+impl SeasonProcessor {
+    /// 获取季度数据中所有已确认 `bgm_id` 的条目详情
+    ///
+    /// 获取失败的条目记录警告后跳过，不影响其他条目
+    pub async fn enrich(&self, data: &SeasonData) -> EnrichedSeason {
+        let mappings = confirmed_mappings(data);
+        // 多个 MAL 条目可能对应同一个 Bangumi 条目
+        let mut subjects: HashMap<u64, Subject> = HashMap::new();
+        let mut items = Vec::with_capacity(mappings.len());
+        let mut failed = 0;
+
+        for (mal_id, bgm_id, status) in mappings {
+            let subject = match subjects.entry(bgm_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => match self.bgm_client.get_subject(bgm_id).await {
+                    Ok(subject) => entry.insert(subject),
+                    Err(e) => {
+                        warn!(bgm_id = bgm_id, error = %e, "获取 Bangumi 条目详情失败");
+                        failed += 1;
+                        continue;
+                    }
+                },
+            };
+            items.push(EnrichedItem::new(mal_id, status, subject));
+        }
+
+        info!(
+            season = %data.season,
+            enriched = items.len(),
+            failed = failed,
+            "补充条目详情完成"
+        );
+        EnrichedSeason {
+            season: data.season,
+            update_time: SeasonData::new(data.season).update_time,
+            items,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::SeasonItem;
+    use crate::core::test_util::{mal, with_bgm};
+    use crate::services::mal::Season;
+
+    #[test]
+    fn test_enriched_item_from_subject() {
+        let subject: Subject = serde_json::from_value(serde_json::json!({
+            "id": 400602,
+            "type": 2,
+            "name": "葬送のフリーレン",
+            "name_cn": "",
+            "date": "2023-09-29",
+            "platform": "TV",
+            "eps": 0,
+            "total_episodes": 28,
+            "rating": { "rank": 15, "total": 12345, "score": 8.5 },
+            "infobox": [{ "key": "动画制作", "value": "マッドハウス" }]
+        }))
+        .unwrap();
+
+        let item = EnrichedItem::new(52991, ConfirmStatus::Match, &subject);
+        assert_eq!(item.bgm_id, 400602);
+        assert_eq!(item.name_cn, None);
+        assert_eq!(item.date.as_deref(), Some("2023-09-29"));
+        assert_eq!(item.eps, Some(28));
+        assert_eq!(item.platform.as_deref(), Some("TV"));
+        assert_eq!(item.score, Some(8.5));
+        assert_eq!(item.rank, Some(15));
+        assert_eq!(item.studios, vec!["マッドハウス"]);
+
+        // 未上榜的条目
+        let subject: Subject = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "type": 2,
+            "rating": { "rank": 0, "total": 3, "score": 0 },
+            "infobox": [
+                { "key": "话数", "value": "12" },
+                { "key": "放送开始", "value": "2026年1月9日" }
+            ]
+        }))
+        .unwrap();
+        let item = EnrichedItem::new(1, ConfirmStatus::Human, &subject);
+        assert_eq!((item.score, item.rank), (None, None));
+        // 条目没有日期与话数时取信息框
+        assert_eq!(item.date.as_deref(), Some("2026-01-09"));
+        assert_eq!(item.eps, Some(12));
+        let json = serde_json::to_value(&item).unwrap();
+        assert!(json.get("score").is_none());
+    }
+
+    #[test]
+    fn test_confirmed_mappings() {
+        let mut data = SeasonData::new(YearSeason::new(2026, Season::Winter));
+        data.items = vec![
+            with_bgm(ConfirmStatus::Match, 1),
+            with_bgm(ConfirmStatus::Human, 2),
+            // 未确认条目的 bgm_id 只是预选建议
+            with_bgm(ConfirmStatus::Unconfirmed, 3),
+            SeasonItem::new(ConfirmStatus::Skip, mal(4)),
+        ];
+
+        assert_eq!(
+            confirmed_mappings(&data),
+            vec![(1, 10, ConfirmStatus::Match), (2, 20, ConfirmStatus::Human)]
+        );
+    }
+}
@@ -0,0 +1,582 @@
+use super::rng::shuffle;
+use super::{
+    ConfirmStatus, ConfirmedMappings, CoreError, MalInfo, MediaType, PendingMatch, SearchOutcome,
+    SeasonData, SeasonItem, SeasonProcessor, YearSeason, save_json, search_air_date,
+};
+use crate::services::mal::{AlternativeTitles, AnimeNode};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tracing::{debug, info};
+
+/// 评估样本：发布数据中已确认的条目，以保存的 `bgm_id` 为真值
+#[derive(Debug, Clone)]
+pub struct EvalCase {
+    pub season: YearSeason,
+    /// 真值来源（human/match/model）
+    pub status: ConfirmStatus,
+    pub bgm_id: u64,
+    pub bgm_name: Option<String>,
+    pub mal: MalInfo,
+}
+
+/// 发布目录中的季度文件（`release/{year}/{year}-{season}-mal.json`），按路径排序
+pub(super) async fn season_files(release_dir: &Path) -> Result<Vec<PathBuf>, CoreError> {
+    let mut paths = Vec::new();
+    let mut years = fs::read_dir(release_dir).await?;
+    while let Some(year) = years.next_entry().await? {
+        if !year.file_type().await?.is_dir() {
+            continue;
+        }
+        let mut files = fs::read_dir(year.path()).await?;
+        while let Some(file) = files.next_entry().await? {
+            let path = file.path();
+            if YearSeason::from_release_file(&path).is_some() {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// 读取发布目录中指定状态的条目
+pub async fn load_ground_truth(
+    release_dir: &Path,
+    statuses: &[ConfirmStatus],
+) -> Result<Vec<EvalCase>, CoreError> {
+    let mut cases = Vec::new();
+    for path in season_files(release_dir).await? {
+        let Some(data) = SeasonData::load(&path).await? else {
+            continue;
+        };
+        let season = data.season;
+        cases.extend(data.items.into_iter().filter_map(|item| {
+            let bgm_id = item.bgm_id?;
+            statuses.contains(&item.status).then_some(EvalCase {
+                season,
+                status: item.status,
+                bgm_id,
+                bgm_name: item.bgm_name,
+                mal: item.mal,
+            })
+        }));
+    }
+
+    Ok(cases)
+}
+
+/// 按种子抽取样本（同一种子结果固定），结果按季度排序以便按季度重放
+pub fn sample(mut cases: Vec<EvalCase>, size: usize, seed: u64) -> Vec<EvalCase> {
+    if size < cases.len() {
+        shuffle(&mut cases, seed);
+        cases.truncate(size);
+    }
+    cases.sort_by_key(|c| (c.season, c.mal.id));
+    cases
+}
+
+/// 给出预测的匹配层级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatcherTier {
+    /// 日文标题完全一致
+    Exact,
+    /// 别名规范化后一致
+    Alias,
+    /// 模型从候选中选择
+    Model,
+    /// 工具调用匹配（模型自行搜索）
+    Agent,
+    /// 模型低置信度，只预选不确认
+    LowConfidence,
+    /// 由前作映射推断续作，只预选不确认
+    Relation,
+    /// 未给出预测
+    Abstain,
+    /// 查询出错
+    Error,
+}
+
+impl MatcherTier {
+    /// 该层级的结果会被自动确认
+    fn is_answer(&self) -> bool {
+        matches!(
+            self,
+            MatcherTier::Exact | MatcherTier::Alias | MatcherTier::Model | MatcherTier::Agent
+        )
+    }
+}
+
+/// 单个样本的重放结果
+#[derive(Debug, Clone)]
+pub struct EvalOutcome {
+    pub case: EvalCase,
+    pub tier: MatcherTier,
+    pub predicted: Option<u64>,
+    pub predicted_name: Option<String>,
+}
+
+impl EvalOutcome {
+    fn new(case: EvalCase, item: SeasonItem) -> Self {
+        let tier = match item.status {
+            ConfirmStatus::Match if item.alias_match.is_some() => MatcherTier::Alias,
+            ConfirmStatus::Match => MatcherTier::Exact,
+            ConfirmStatus::Model if item.llm.as_ref().is_some_and(|d| !d.searches.is_empty()) => {
+                MatcherTier::Agent
+            }
+            ConfirmStatus::Model => MatcherTier::Model,
+            ConfirmStatus::Error => MatcherTier::Error,
+            _ if item.relation.is_some() => MatcherTier::Relation,
+            _ if item.bgm_id.is_some() => MatcherTier::LowConfidence,
+            _ => MatcherTier::Abstain,
+        };
+        Self {
+            case,
+            tier,
+            predicted: item.bgm_id,
+            predicted_name: item.bgm_name,
+        }
+    }
+
+    fn is_correct(&self) -> bool {
+        self.predicted == Some(self.case.bgm_id)
+    }
+}
+
+/// 评估指标
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct EvalMetrics {
+    pub total: u32,
+    /// 自动确认的数量
+    pub answered: u32,
+    /// 自动确认且正确的数量
+    pub correct: u32,
+    /// 未自动确认（低置信度预选或无预测）
+    pub abstained: u32,
+    /// 预选的数量（低置信度或续作推断）
+    pub preselected: u32,
+    /// 预选且正确的数量
+    pub preselected_correct: u32,
+    pub errors: u32,
+}
+
+impl EvalMetrics {
+    fn add(&mut self, outcome: &EvalOutcome) {
+        self.total += 1;
+        match outcome.tier {
+            MatcherTier::Error => self.errors += 1,
+            tier if tier.is_answer() => {
+                self.answered += 1;
+                if outcome.is_correct() {
+                    self.correct += 1;
+                }
+            }
+            tier => {
+                self.abstained += 1;
+                if matches!(tier, MatcherTier::LowConfidence | MatcherTier::Relation) {
+                    self.preselected += 1;
+                    if outcome.is_correct() {
+                        self.preselected_correct += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 精确率：自动确认中正确的比例
+    pub fn precision(&self) -> Option<f64> {
+        ratio(self.correct, self.answered)
+    }
+
+    /// 召回率：全部样本中被正确自动确认的比例
+    pub fn recall(&self) -> Option<f64> {
+        ratio(self.correct, self.total)
+    }
+
+    /// 弃权率：全部样本中未自动确认（不含出错）的比例
+    pub fn abstention_rate(&self) -> Option<f64> {
+        ratio(self.abstained, self.total)
+    }
+}
+
+fn ratio(numerator: u32, denominator: u32) -> Option<f64> {
+    (denominator > 0).then(|| numerator as f64 / denominator as f64)
+}
+
+/// 预测与真值不一致的条目
+#[derive(Debug, Clone, Serialize)]
+pub struct Confusion {
+    pub season: YearSeason,
+    pub mal_id: u64,
+    pub mal_title: String,
+    pub tier: MatcherTier,
+    pub expected: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_name: Option<String>,
+    pub predicted: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub predicted_name: Option<String>,
+}
+
+/// 评估报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EvalReport {
+    pub overall: EvalMetrics,
+    pub by_tier: BTreeMap<MatcherTier, EvalMetrics>,
+    pub by_media_type: BTreeMap<MediaType, EvalMetrics>,
+    /// 按真值来源统计（`match` 真值来自完全匹配，对 exact 层级天然有利）
+    pub by_truth: BTreeMap<ConfirmStatus, EvalMetrics>,
+    /// 错误的自动确认与错误的预选
+    pub confusions: Vec<Confusion>,
+}
+
+impl EvalReport {
+    pub fn from_outcomes(outcomes: &[EvalOutcome]) -> Self {
+        let mut report = EvalReport::default();
+        for outcome in outcomes {
+            report.overall.add(outcome);
+            report.by_tier.entry(outcome.tier).or_default().add(outcome);
+            report
+                .by_media_type
+                .entry(outcome.case.mal.media_type)
+                .or_default()
+                .add(outcome);
+            report
+                .by_truth
+                .entry(outcome.case.status)
+                .or_default()
+                .add(outcome);
+
+            if let Some(predicted) = outcome.predicted.filter(|_| !outcome.is_correct()) {
+                report.confusions.push(Confusion {
+                    season: outcome.case.season,
+                    mal_id: outcome.case.mal.id,
+                    mal_title: outcome.case.mal.title.clone(),
+                    tier: outcome.tier,
+                    expected: outcome.case.bgm_id,
+                    expected_name: outcome.case.bgm_name.clone(),
+                    predicted,
+                    predicted_name: outcome.predicted_name.clone(),
+                });
+            }
+        }
+        report
+    }
+
+    /// 保存到文件
+    pub async fn save(&self, path: &Path) -> Result<(), CoreError> {
+        save_json(self, path).await
+    }
+}
+
+impl fmt::Display for EvalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn percent(value: Option<f64>) -> String {
+            value.map_or("-".to_string(), |v| format!("{:.1}%", v * 100.0))
+        }
+
+        fn table<K: Serialize>(
+            f: &mut fmt::Formatter<'_>,
+            title: &str,
+            rows: impl IntoIterator<Item = (K, EvalMetrics)>,
+        ) -> fmt::Result {
+            writeln!(f, "## {}\n", title)?;
+            writeln!(
+                f,
+                "| 分组 | 样本 | 确认 | 正确 | 精确率 | 召回率 | 弃权率 | 预选正确 | 出错 |"
+            )?;
+            writeln!(f, "| --- | --- | --- | --- | --- | --- | --- | --- | --- |")?;
+            for (key, m) in rows {
+                writeln!(
+                    f,
+                    "| {} | {} | {} | {} | {} | {} | {} | {}/{} | {} |",
+                    label(&key),
+                    m.total,
+                    m.answered,
+                    m.correct,
+                    percent(m.precision()),
+                    percent(m.recall()),
+                    percent(m.abstention_rate()),
+                    m.preselected_correct,
+                    m.preselected,
+                    m.errors
+                )?;
+            }
+            writeln!(f)
+        }
+
+        table(f, "总体", [("all", self.overall)])?;
+        table(f, "按匹配层级", self.by_tier.clone())?;
+        table(f, "按 media_type", self.by_media_type.clone())?;
+        table(f, "按真值来源", self.by_truth.clone())?;
+
+        writeln!(f, "## 错误匹配（{}）\n", self.confusions.len())?;
+        if self.confusions.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "| 季度 | MAL | 层级 | 真值 | 预测 |")?;
+        writeln!(f, "| --- | --- | --- | --- | --- |")?;
+        for c in &self.confusions {
+            writeln!(
+                f,
+                "| {} | {} {} | {} | {} {} | {} {} |",
+                c.season,
+                c.mal_id,
+                c.mal_title,
+                label(&c.tier),
+                c.expected,
+                c.expected_name.as_deref().unwrap_or_default(),
+                c.predicted,
+                c.predicted_name.as_deref().unwrap_or_default()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 分组名称（与 JSON 报告中的键一致）
+fn label<T: Serialize>(key: &T) -> String {
+    match serde_json::to_value(key) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+/// MAL 请求失败（如重放时未缓存）时，由发布数据构建最简的番组信息
+fn anime_from_mal_info(mal: &MalInfo) -> AnimeNode {
+    AnimeNode {
+        id: mal.id,
+        title: mal.title.clone(),
+        main_picture: None,
+        alternative_titles: Some(AlternativeTitles {
+            en: None,
+            ja: mal.title_ja.clone(),
+            synonyms: vec![],
+        }),
+        start_date: None,
+        end_date: None,
+        synopsis: None,
+        media_type: Some(mal.media_type.as_mal_str().to_string()),
+        status: None,
+        num_episodes: None,
+        start_season: None,
+        broadcast: None,
+        source: None,
+        studios: vec![],
+        rating: None,
+    }
+}
+
+impl SeasonProcessor {
+    /// 在样本上重放匹配流程（搜索、完全匹配、模型判断、工具调用匹配，启用时还有续作推断）
+    ///
+    /// 样本须按季度排序；每个季度的待定条目与正式运行一样统一批量请求 LLM。
+    /// 续作推断使用 `release_dir` 中已确认的映射
+    pub async fn replay(
+        &self,
+        release_dir: &Path,
+        cases: &[EvalCase],
+    ) -> Result<Vec<EvalOutcome>, CoreError> {
+        let mappings = match self.relation_walk {
+            true => Some(ConfirmedMappings::load(release_dir).await?),
+            false => None,
+        };
+        let mut outcomes = Vec::with_capacity(cases.len());
+
+        for group in cases.chunk_by(|a, b| a.season == b.season) {
+            let season = group[0].season;
+            info!(season = %season, count = group.len(), "重放季度样本");
+            let mals: Vec<_> = group.iter().map(|case| case.mal.clone()).collect();
+            let items = self.rerun(season, mals, mappings.as_ref()).await;
+            outcomes.extend(
+                group
+                    .iter()
+                    .cloned()
+                    .zip(items)
+                    .map(|(case, item)| EvalOutcome::new(case, item)),
+            );
+        }
+
+        Ok(outcomes)
+    }
+
+    /// 对同一季度的条目重新运行匹配流程，返回与 `mals` 一一对应的结果
+    ///
+    /// MAL 详情（模型判断用的元数据）获取失败时使用发布数据中的信息；
+    /// 给出 `mappings` 时仍没有结果的条目再由前作映射推断续作
+    pub(super) async fn rerun(
+        &self,
+        season: YearSeason,
+        mals: Vec<MalInfo>,
+        mappings: Option<&ConfirmedMappings>,
+    ) -> Vec<SeasonItem> {
+        let mut items = Vec::with_capacity(mals.len());
+        let mut pending = Vec::new();
+        for mal in mals {
+            let anime = match self.mal_client.get_anime(mal.id).await {
+                Ok(anime) => anime,
+                Err(e) => {
+                    debug!(mal_id = mal.id, error = %e, "获取 MAL 详情失败，使用发布数据");
+                    anime_from_mal_info(&mal)
+                }
+            };
+            let air_date = search_air_date(&anime, season);
+            match self.search_and_match(&anime, mal, air_date).await {
+                SearchOutcome::Done(item) => items.push(item),
+                SearchOutcome::Pending(item, subjects) => {
+                    pending.push(PendingMatch {
+                        index: items.len(),
+                        anime,
+                        subjects,
+                    });
+                    items.push(item);
+                }
+            }
+        }
+
+        self.resolve_pending(&mut items, &pending).await;
+        if let Some(mappings) = mappings {
+            self.resolve_with_relations(season, &mut items, mappings)
+                .await;
+        }
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_util::mal;
+    use crate::services::mal::Season;
+
+    fn case(mal_id: u64, bgm_id: u64, media_type: MediaType) -> EvalCase {
+        EvalCase {
+            season: YearSeason::new(2024, Season::Spring),
+            status: ConfirmStatus::Human,
+            bgm_id,
+            bgm_name: None,
+            mal: MalInfo {
+                media_type,
+                ..mal(mal_id)
+            },
+        }
+    }
+
+    fn outcome(case: EvalCase, tier: MatcherTier, predicted: Option<u64>) -> EvalOutcome {
+        EvalOutcome {
+            case,
+            tier,
+            predicted,
+            predicted_name: None,
+        }
+    }
+
+    #[test]
+    fn test_sample_is_deterministic() {
+        let cases: Vec<_> = (0..50).map(|i| case(i, i, MediaType::Tv)).collect();
+
+        let ids = |cases: Vec<EvalCase>| cases.iter().map(|c| c.mal.id).collect::<Vec<_>>();
+        let a = ids(sample(cases.clone(), 10, 7));
+        assert_eq!(a.len(), 10);
+        assert_eq!(a, ids(sample(cases.clone(), 10, 7)));
+        assert_ne!(a, ids(sample(cases.clone(), 10, 8)));
+        assert!(a.windows(2).all(|w| w[0] < w[1]));
+
+        // 样本数不小于总数时保留全部
+        assert_eq!(sample(cases, 100, 7).len(), 50);
+    }
+
+    #[test]
+    fn test_eval_report() {
+        let outcomes = vec![
+            outcome(case(1, 10, MediaType::Tv), MatcherTier::Exact, Some(10)),
+            outcome(case(2, 20, MediaType::Tv), MatcherTier::Model, Some(21)),
+            outcome(case(3, 30, MediaType::Movie), MatcherTier::Model, Some(30)),
+            outcome(
+                case(4, 40, MediaType::Movie),
+                MatcherTier::LowConfidence,
+                Some(40),
+            ),
+            outcome(case(5, 50, MediaType::Ova), MatcherTier::Abstain, None),
+            outcome(case(6, 60, MediaType::Ova), MatcherTier::Error, None),
+        ];
+        let report = EvalReport::from_outcomes(&outcomes);
+
+        let overall = report.overall;
+        assert_eq!(overall.total, 6);
+        assert_eq!(overall.answered, 3);
+        assert_eq!(overall.correct, 2);
+        assert_eq!(overall.abstained, 2);
+        assert_eq!(overall.preselected_correct, 1);
+        assert_eq!(overall.errors, 1);
+        assert!((overall.precision().unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        assert!((overall.recall().unwrap() - 2.0 / 6.0).abs() < 1e-9);
+        assert!((overall.abstention_rate().unwrap() - 2.0 / 6.0).abs() < 1e-9);
+
+        assert_eq!(report.by_tier[&MatcherTier::Model].answered, 2);
+        assert_eq!(report.by_tier[&MatcherTier::Model].correct, 1);
+        assert_eq!(report.by_media_type[&MediaType::Movie].total, 2);
+        assert_eq!(report.by_media_type[&MediaType::Ova].precision(), None);
+        assert_eq!(report.by_truth[&ConfirmStatus::Human].total, 6);
+
+        assert_eq!(report.confusions.len(), 1);
+        assert_eq!(report.confusions[0].mal_id, 2);
+        assert_eq!(report.confusions[0].predicted, 21);
+
+        let text = report.to_string();
+        assert!(text.contains("| all | 6 | 3 | 2 | 66.7% | 33.3% | 33.3% | 1/1 | 1 |"));
+        assert!(text.contains("| model | 2 | 2 | 1 | 50.0% |"));
+        assert!(text.contains("| low_confidence | 1 | 0 | 0 |"));
+        assert!(text.contains("## 错误匹配（1）"));
+    }
+
+    #[test]
+    fn test_outcome_tier_from_item() {
+        let mal = case(1, 10, MediaType::Tv).mal;
+        let unconfirmed = SeasonItem::new(ConfirmStatus::Unconfirmed, mal.clone());
+        assert_eq!(
+            EvalOutcome::new(case(1, 10, MediaType::Tv), unconfirmed.clone()).tier,
+            MatcherTier::Abstain
+        );
+
+        let preselected = SeasonItem {
+            bgm_id: Some(10),
+            ..unconfirmed
+        };
+        assert_eq!(
+            EvalOutcome::new(case(1, 10, MediaType::Tv), preselected.clone()).tier,
+            MatcherTier::LowConfidence
+        );
+
+        let related = SeasonItem {
+            relation: Some(crate::core::RelationMatch {
+                prequel_mal_id: 2,
+                prequel_season: YearSeason::new(2024, Season::Fall),
+                prequel_bgm_id: 20,
+                chain: vec![],
+            }),
+            ..preselected
+        };
+        let outcome = EvalOutcome::new(case(1, 10, MediaType::Tv), related);
+        assert_eq!(outcome.tier, MatcherTier::Relation);
+        let mut metrics = EvalMetrics::default();
+        metrics.add(&outcome);
+        assert_eq!(
+            (
+                metrics.answered,
+                metrics.preselected,
+                metrics.preselected_correct
+            ),
+            (0, 1, 1)
+        );
+
+        let error = SeasonItem::new(ConfirmStatus::Error, mal);
+        assert_eq!(
+            EvalOutcome::new(case(1, 10, MediaType::Tv), error).tier,
+            MatcherTier::Error
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use super::eval::season_files;
+use super::{CoreError, MalInfo, SeasonData, YearSeason};
+use crate::services::mal::AnimeNode;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// 番组与当前季度的关系
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Membership {
+    /// 本季新番
+    New,
+    /// 续播番组（之前季度开播）或不属于本季
+    CarryOver,
+    /// 无法判断，写入季度数据的 `unclassified`
+    Unknown,
+}
+
+/// 无法判断是否为本季新番的条目（MAL 没有 `start_season` 与 `start_date`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnclassifiedItem {
+    pub mal: MalInfo,
+    /// MAL 给出的不完整日期（只有年份）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+}
+
+/// 由 `start_date` 推断开播季度（至少要有年月）
+fn season_of_date(date: &str) -> Option<YearSeason> {
+    let mut parts = date.trim().split('-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    YearSeason::from_year_month(year, month)
+}
+
+/// 番组的开播季度（`start_season`，没有时由 `start_date` 推断）
+pub(crate) fn start_season(anime: &AnimeNode) -> Option<YearSeason> {
+    anime
+        .start_season
+        .as_ref()
+        .map(|s| YearSeason::new(s.year, s.season))
+        .or_else(|| anime.start_date.as_deref().and_then(season_of_date))
+}
+
+/// 发布数据中各 MAL ID 首次收录的季度（`items`、`unclassified`、`carry_over`）
+///
+/// 默认不记录续播条目，长期放送的番组可能只在首次出现的季度有记录，因此要看所有季度。
+/// 每次运行读取一次，处理完一个季度后由 [`SeasonHistory::record`] 更新
+#[derive(Debug, Default)]
+pub struct SeasonHistory {
+    first: HashMap<u64, YearSeason>,
+}
+
+impl SeasonHistory {
+    /// 读取发布目录中所有季度文件
+    pub async fn load(release_dir: &Path) -> Result<Self, CoreError> {
+        let mut history = Self::default();
+        if !release_dir.exists() {
+            return Ok(history);
+        }
+        for path in season_files(release_dir).await? {
+            if let Some(data) = SeasonData::load(&path).await? {
+                history.record(&data);
+            }
+        }
+        Ok(history)
+    }
+
+    /// 记录季度数据中收录的条目
+    pub fn record(&mut self, data: &SeasonData) {
+        for id in data.recorded_mal_ids() {
+            let first = self.first.entry(id).or_insert(data.season);
+            *first = (*first).min(data.season);
+        }
+    }
+
+    /// `season` 之前首次收录该条目的季度
+    pub(crate) fn reported_before(&self, mal_id: u64, season: YearSeason) -> Option<YearSeason> {
+        self.first
+            .get(&mal_id)
+            .copied()
+            .filter(|&first| first < season)
+    }
+}
+
+/// 判断番组是否为本季新番
+///
+/// 依次使用 `start_season`、`start_date`（年月所在季度）；都没有时看发布数据：
+/// 之前的季度已收录为续播；当前季度的数据已将其列为条目（`current`，之前运行时判断为新番
+/// 或人工从 `unclassified` 移入）为新番；其他情况之前的列表没有收录不能说明是新番，无法判断
+pub(crate) fn classify(
+    anime: &AnimeNode,
+    season: YearSeason,
+    history: &SeasonHistory,
+    current: &HashSet<u64>,
+) -> Membership {
+    if let Some(start) = start_season(anime) {
+        return if start == season {
+            Membership::New
+        } else {
+            Membership::CarryOver
+        };
+    }
+
+    if history.reported_before(anime.id, season).is_some() {
+        Membership::CarryOver
+    } else if current.contains(&anime.id) {
+        Membership::New
+    } else {
+        Membership::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_util::mal;
+    use crate::core::{ConfirmStatus, SeasonItem};
+    use crate::services::mal::{Season, StartSeason};
+
+    const SPRING: YearSeason = YearSeason {
+        year: 2024,
+        season: Season::Spring,
+    };
+
+    fn anime(start_season: Option<(u32, Season)>, start_date: Option<&str>) -> AnimeNode {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "title": "anime",
+            "start_season": start_season.map(|(year, season)| StartSeason { year, season }),
+            "start_date": start_date,
+        }))
+        .unwrap()
+    }
+
+    /// 只由开播季度、日期判断（没有发布数据）
+    fn classify_by_date(anime: &AnimeNode, season: YearSeason) -> Membership {
+        classify(anime, season, &SeasonHistory::default(), &HashSet::new())
+    }
+
+    #[test]
+    fn test_classify_by_start_season() {
+        let spring = Some((2024, Season::Spring));
+        assert_eq!(
+            classify_by_date(&anime(spring, None), SPRING),
+            Membership::New
+        );
+        // start_season 优先于 start_date
+        assert_eq!(
+            classify_by_date(
+                &anime(spring, Some("2024-03-30")),
+                YearSeason::new(2024, Season::Summer)
+            ),
+            Membership::CarryOver
+        );
+    }
+
+    #[test]
+    fn test_classify_by_start_date() {
+        assert_eq!(
+            classify_by_date(&anime(None, Some("2024-04-12")), SPRING),
+            Membership::New
+        );
+        assert_eq!(
+            classify_by_date(&anime(None, Some("2024-04")), SPRING),
+            Membership::New
+        );
+        assert_eq!(
+            classify_by_date(&anime(None, Some("2024-01-05")), SPRING),
+            Membership::CarryOver
+        );
+    }
+
+    #[test]
+    fn test_classify_by_history() {
+        let winter = YearSeason::new(2024, Season::Winter);
+        let mut data = SeasonData::new(winter);
+        data.unclassified = vec![UnclassifiedItem {
+            mal: mal(1),
+            start_date: None,
+        }];
+        data.items = vec![SeasonItem::new(ConfirmStatus::Unconfirmed, mal(2))];
+        let mut history = SeasonHistory::default();
+        history.record(&data);
+
+        // 只有年份的日期无法判断季度
+        let unknown = anime(None, Some("2024"));
+        assert_eq!(
+            classify(&unknown, SPRING, &history, &HashSet::new()),
+            Membership::CarryOver
+        );
+        // 之前的季度还没有收录
+        assert_eq!(
+            classify(&unknown, winter, &history, &HashSet::new()),
+            Membership::Unknown
+        );
+        // 没有任何数据
+        assert_eq!(classify_by_date(&unknown, SPRING), Membership::Unknown);
+        // 当前季度已列为条目
+        assert_eq!(
+            classify(&unknown, winter, &history, &HashSet::from([1])),
+            Membership::New
+        );
+    }
+
+    #[test]
+    fn test_classify_absent_from_history() {
+        // 有之前季度的数据，但没有收录该条目，也没有开播日期：不能视为新番
+        let mut history = SeasonHistory::default();
+        let mut data = SeasonData::new(YearSeason::new(2024, Season::Winter));
+        data.items = vec![SeasonItem::new(ConfirmStatus::Human, mal(2))];
+        history.record(&data);
+
+        assert_eq!(
+            classify(&anime(None, None), SPRING, &history, &HashSet::new()),
+            Membership::Unknown
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undated_show_across_three_seasons() {
+        let dir =
+            std::env::temp_dir().join(format!("season-data-membership-{}", std::process::id()));
+        let winter = YearSeason::new(2024, Season::Winter);
+        let summer = YearSeason::new(2024, Season::Summer);
+        let undated = anime(None, None);
+
+        // 冬季首次出现并收录；春季按续播处理，默认不记录续播，春季文件中没有该条目
+        let mut data = SeasonData::new(winter);
+        data.items = vec![SeasonItem::new(ConfirmStatus::Unconfirmed, mal(1))];
+        data.save(&winter.release_file(&dir)).await.unwrap();
+        let mut history = SeasonHistory::load(&dir).await.unwrap();
+        assert_eq!(
+            classify(&undated, SPRING, &history, &HashSet::new()),
+            Membership::CarryOver
+        );
+        history.record(&SeasonData::new(SPRING));
+
+        // 夏季仍是续播（不会因为春季没有记录而变成新番或无法判断）
+        assert_eq!(history.reported_before(1, summer), Some(winter));
+        assert_eq!(
+            classify(&undated, summer, &history, &HashSet::new()),
+            Membership::CarryOver
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
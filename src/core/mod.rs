@@ -1,9 +1,54 @@
-use crate::services::bgmtv::BgmtvClient;
-use crate::services::ds::DsClient;
-use crate::services::mal::{AnimeNode, MalClient, Season};
+mod agent;
+mod air_date;
+mod calendar;
+mod candidate_filter;
+mod carry_over;
+mod consensus;
+mod enrich;
+mod eval;
+mod membership;
+mod relation;
+mod rematch;
+mod rng;
+mod studio;
+#[cfg(test)]
+mod test_util;
+mod title_match;
+mod verify;
+mod year_season;
+
+pub use agent::{AgentError, AgentMatch, DEFAULT_AGENT_MAX_TURNS, MatchAgent};
+pub use air_date::{AirDateRange, DateTolerance};
+pub use calendar::{CalendarEntry, CalendarReport, MissingFromCalendar};
+pub use candidate_filter::FilterReason;
+pub use carry_over::CarryOverItem;
+use carry_over::SeasonArchive;
+pub use consensus::{Consensus, ConsensusStats};
+pub use enrich::{EnrichedItem, EnrichedSeason};
+pub use eval::{
+    Confusion, EvalCase, EvalMetrics, EvalOutcome, EvalReport, MatcherTier, load_ground_truth,
+    sample,
+};
+use membership::Membership;
+pub use membership::{SeasonHistory, UnclassifiedItem};
+use relation::ConfirmedMappings;
+pub use relation::{RelationMatch, RelationStep};
+pub use rematch::RematchStats;
+pub use title_match::AliasMatch;
+pub use verify::{
+    DEFAULT_VERIFY_CACHE_MAX_AGE, DEFAULT_VERIFY_CONCURRENCY, VerifyFinding, VerifyIssue,
+    VerifyReport,
+};
+pub use year_season::{DEFAULT_SEASON_TIMEZONE, SeasonRange, YearSeason, parse_utc_offset};
+
+use crate::services::bgmtv::{BgmtvClient, Subject};
+use crate::services::llm::{BatchItem, CandidateEntry, LlmClient, MalEntry, MatchDecision};
+use crate::services::mal::{AnimeNode, MalClient};
 use chrono::Local;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::Path;
+use std::sync::Mutex;
 use thiserror::Error;
 use tokio::fs;
 use tracing::{debug, info, warn};
@@ -21,13 +66,15 @@ pub enum CoreError {
 }
 
 /// 确认状态
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum ConfirmStatus {
     /// 未确认
     #[default]
     Unconfirmed,
-    /// 精确匹配（日文标题完全一致）
+    /// 精确匹配（日文标题完全一致，或别名规范化后一致）
     Match,
     /// 模型确认（LLM 判断匹配）
     Model,
@@ -46,6 +93,22 @@ impl ConfirmStatus {
     }
 }
 
+impl std::str::FromStr for ConfirmStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unconfirmed" => Ok(ConfirmStatus::Unconfirmed),
+            "match" => Ok(ConfirmStatus::Match),
+            "model" => Ok(ConfirmStatus::Model),
+            "human" => Ok(ConfirmStatus::Human),
+            "error" => Ok(ConfirmStatus::Error),
+            "skip" => Ok(ConfirmStatus::Skip),
+            _ => Err(format!("unknown status: {}", s)),
+        }
+    }
+}
+
 /// 转换后的 rating 类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -67,7 +130,7 @@ impl Rating {
 }
 
 /// 转换后的 media_type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum MediaType {
     Tv,
@@ -97,6 +160,20 @@ impl MediaType {
         }
     }
 
+    /// 对应的 MAL media_type 字符串
+    pub fn as_mal_str(&self) -> &'static str {
+        match self {
+            MediaType::Tv => "tv",
+            MediaType::Ova => "ova",
+            MediaType::Ona => "ona",
+            MediaType::Movie => "movie",
+            MediaType::Special => "special",
+            MediaType::TvSpecial => "tv_special",
+            MediaType::Music => "music",
+            MediaType::Pv => "pv",
+        }
+    }
+
     /// 是否应该标记为 skip（不需要匹配 Bangumi）
     pub fn should_skip(&self) -> bool {
         matches!(
@@ -113,6 +190,11 @@ pub struct MalInfo {
     pub title: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title_ja: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_en: Option<String>,
+    /// 同义名
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub synonyms: Vec<String>,
     pub media_type: MediaType,
     pub rating: Rating,
 }
@@ -122,28 +204,74 @@ impl MalInfo {
     pub fn from_anime_node(node: &AnimeNode) -> Option<Self> {
         let media_type = MediaType::from_mal(node.media_type.as_deref())?;
         let rating = Rating::from_mal(node.rating.as_deref());
-        let title_ja = node
-            .alternative_titles
-            .as_ref()
-            .and_then(|t| t.ja.clone());
+        let alternative = node.alternative_titles.as_ref();
 
         Some(MalInfo {
             id: node.id,
             title: node.title.clone(),
-            title_ja,
+            title_ja: alternative.and_then(|t| t.ja.clone()),
+            title_en: alternative
+                .and_then(|t| t.en.clone())
+                .filter(|t| !t.is_empty()),
+            synonyms: alternative.map(|t| t.synonyms.clone()).unwrap_or_default(),
             media_type,
             rating,
         })
     }
+
+    /// 所有标题：`title`、日文标题、英文标题、同义名
+    pub fn titles(&self) -> Vec<&str> {
+        std::iter::once(self.title.as_str())
+            .chain(self.title_ja.as_deref())
+            .chain(self.title_en.as_deref())
+            .chain(self.synonyms.iter().map(String::as_str))
+            .collect()
+    }
 }
 
 /// Bangumi 候选条目
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// `bgm_name_cn` 以后的字段供人工复核，旧数据中没有时为空
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BgmCandidate {
     pub bgm_id: u64,
     pub bgm_name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bgm_name_cn: Option<String>,
+    /// 放送开始日期
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// TV/OVA/剧场版/WEB/...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    /// 话数（`eps`，没有时为 `total_episodes`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eps: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nsfw: Option<bool>,
+    /// 封面缩略图
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    /// 与 MAL 标题的相似度（0 ~ 1）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    /// 找到该候选的搜索关键词
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyword: Option<String>,
+    /// 被过滤的原因（不参与匹配，保留供人工查看）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filtered: Option<FilterReason>,
+}
+
+impl BgmCandidate {
+    /// 由搜索结果创建，记录标题相似度与搜索关键词
+    fn scored(subject: &Subject, mal_titles: &[&str], keyword: Option<&str>) -> Self {
+        BgmCandidate {
+            score: Some(title_match::title_similarity(mal_titles, subject)),
+            keyword: keyword.map(str::to_string),
+            ..subject.into()
+        }
+    }
 }
 
 /// 季度条目
@@ -158,30 +286,88 @@ pub struct SeasonItem {
     pub bgm_name_cn: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub candidates: Vec<BgmCandidate>,
+    /// 模型的判断（选中 ID、置信度、理由、服务商与模型）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm: Option<MatchDecision>,
+    /// 共识模式下的第二次判断
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_second: Option<MatchDecision>,
+    /// 由前作映射推断续作的过程
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relation: Option<RelationMatch>,
+    /// 别名匹配时一致的标题
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias_match: Option<AliasMatch>,
     pub mal: MalInfo,
 }
 
+impl SeasonItem {
+    /// 创建不带 Bangumi 信息的条目
+    pub fn new(status: ConfirmStatus, mal: MalInfo) -> Self {
+        SeasonItem {
+            status,
+            bgm_id: None,
+            bgm_name: None,
+            bgm_name_cn: None,
+            candidates: vec![],
+            llm: None,
+            llm_second: None,
+            relation: None,
+            alias_match: None,
+            mal,
+        }
+    }
+
+    /// 创建已匹配到指定候选的条目
+    pub fn matched(status: ConfirmStatus, candidate: BgmCandidate, mal: MalInfo) -> Self {
+        SeasonItem {
+            bgm_id: Some(candidate.bgm_id),
+            bgm_name: Some(candidate.bgm_name),
+            bgm_name_cn: candidate.bgm_name_cn,
+            ..Self::new(status, mal)
+        }
+    }
+}
+
 /// 季度数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SeasonData {
-    pub season: String,
+    pub season: YearSeason,
     pub update_time: String,
     pub items: Vec<SeasonItem>,
+    /// 无法判断是否为本季新番的条目（不参与匹配，留给人工检查）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unclassified: Vec<UnclassifiedItem>,
+    /// 之前季度开播、本季继续放送的番组（启用续播记录时）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub carry_over: Vec<CarryOverItem>,
+}
+
+/// 以格式化的 JSON 保存到文件（自动创建目录）
+pub(crate) async fn save_json<T: Serialize>(value: &T, path: &Path) -> Result<(), CoreError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    let content = serde_json::to_string_pretty(value)?;
+    fs::write(path, content).await?;
+    Ok(())
 }
 
 impl SeasonData {
     /// 创建新的季度数据
-    pub fn new(year: u32, season: Season) -> Self {
-        let season_str = format!("{}-{}", year, season);
+    pub fn new(season: YearSeason) -> Self {
         let update_time = Local::now()
             .fixed_offset()
             .format("%Y-%m-%dT%H:%M:%S%:z")
             .to_string();
 
         SeasonData {
-            season: season_str,
+            season,
             update_time,
             items: Vec::new(),
+            unclassified: Vec::new(),
+            carry_over: Vec::new(),
         }
     }
 
@@ -202,13 +388,7 @@ impl SeasonData {
 
     /// 保存到文件
     pub async fn save(&self, path: &Path) -> Result<(), CoreError> {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).await?;
-        }
-
-        let content = serde_json::to_string_pretty(self)?;
-        fs::write(path, content).await?;
-        Ok(())
+        save_json(self, path).await
     }
 
     /// 获取已确认的 MAL ID 集合
@@ -219,27 +399,221 @@ impl SeasonData {
             .map(|item| item.mal.id)
             .collect()
     }
+
+    /// 收录的所有 MAL ID（包括无法判断季度的条目与续播条目）
+    pub fn recorded_mal_ids(&self) -> std::collections::HashSet<u64> {
+        self.items
+            .iter()
+            .map(|item| item.mal.id)
+            .chain(self.unclassified.iter().map(|u| u.mal.id))
+            .chain(self.carry_over.iter().map(|c| c.mal.id))
+            .collect()
+    }
 }
 
-/// 获取季度的日期范围
-pub fn season_date_range(year: u32, season: Season) -> (String, String) {
-    match season {
-        // 冬季: 12月1日 ~ 3月31日
-        Season::Winter => (format!("{}-12-01", year - 1), format!("{}-03-31", year)),
-        // 春季: 3月1日 ~ 6月30日
-        Season::Spring => (format!("{}-03-01", year), format!("{}-06-30", year)),
-        // 夏季: 6月1日 ~ 9月30日
-        Season::Summer => (format!("{}-06-01", year), format!("{}-09-30", year)),
-        // 秋季: 9月1日 ~ 12月31日
-        Season::Fall => (format!("{}-09-01", year), format!("{}-12-31", year)),
+/// 搜索 Bangumi 的基准日期：MAL 开播日期，没有时为季度范围
+fn search_air_date(anime: &AnimeNode, season: YearSeason) -> AirDateRange {
+    anime
+        .start_date
+        .as_deref()
+        .and_then(AirDateRange::from_mal_date)
+        .unwrap_or_else(|| AirDateRange::season(season))
+}
+
+/// 等待 LLM 验证的条目
+struct PendingMatch {
+    /// 在 `SeasonData.items` 中的下标
+    index: usize,
+    anime: AnimeNode,
+    /// 未被过滤的 Bangumi 搜索结果（与条目 candidates 中 `filtered` 为空的部分顺序一致；
+    /// 被过滤的候选排在其后，不在此列，不写入提示）
+    subjects: Vec<Subject>,
+}
+
+impl PendingMatch {
+    /// 提示中的 MAL 条目信息
+    fn mal_entry<'a>(&'a self, mal: &'a MalInfo) -> MalEntry<'a> {
+        MalEntry {
+            title: &mal.title,
+            title_ja: mal.title_ja.as_deref(),
+            media_type: self.anime.media_type.as_deref(),
+            start_date: self.anime.start_date.as_deref(),
+            num_episodes: self.anime.num_episodes,
+            studios: mal_studios(&self.anime),
+        }
+    }
+
+    /// 提示中的 Bangumi 候选信息
+    fn candidate_entries(&self) -> Vec<CandidateEntry<'_>> {
+        let mal_studios = mal_studios(&self.anime);
+        self.subjects
+            .iter()
+            .map(|s| candidate_entry(s, &mal_studios))
+            .collect()
+    }
+}
+
+/// MAL 制作公司
+fn mal_studios(anime: &AnimeNode) -> Vec<&str> {
+    anime.studios.iter().map(|s| s.name.as_str()).collect()
+}
+
+/// 提示中的 Bangumi 候选信息（`mal_studios` 用于标注制作公司是否一致）
+fn candidate_entry<'a>(subject: &'a Subject, mal_studios: &[&str]) -> CandidateEntry<'a> {
+    let studios = subject.infobox_fields().studios;
+    CandidateEntry {
+        studio_match: studio::studios_agree(mal_studios, &studios),
+        studios,
+        id: subject.id,
+        name: subject.name.as_deref().unwrap_or_default(),
+        name_cn: subject.name_cn.as_deref(),
+        date: subject.date.as_deref(),
+        eps: subject.eps,
+        platform: subject.platform.as_deref(),
+    }
+}
+
+impl From<&Subject> for BgmCandidate {
+    fn from(s: &Subject) -> Self {
+        let non_empty = |value: &Option<String>| value.clone().filter(|v| !v.is_empty());
+        let thumbnail = s
+            .images
+            .as_ref()
+            .and_then(|images| non_empty(&images.small).or_else(|| non_empty(&images.grid)));
+        BgmCandidate {
+            bgm_id: s.id,
+            bgm_name: s.name.clone().unwrap_or_default(),
+            bgm_name_cn: s.name_cn.clone(),
+            date: non_empty(&s.date),
+            platform: non_empty(&s.platform),
+            eps: s
+                .eps
+                .filter(|&eps| eps > 0)
+                .or(s.total_episodes.filter(|&eps| eps > 0)),
+            nsfw: s.nsfw,
+            thumbnail,
+            score: None,
+            keyword: None,
+            filtered: None,
+        }
+    }
+}
+
+/// 搜索匹配的结果
+enum SearchOutcome {
+    /// 已完成（完全匹配或查询出错）
+    Done(SeasonItem),
+    /// 未确认，等待模型判断（搜索结果可能为空）
+    Pending(SeasonItem, Vec<Subject>),
+}
+
+/// 别名匹配自动确认的放送日期范围：开播日期按最小的搜索容差放宽
+fn alias_window(air_date: AirDateRange, tolerances: &[DateTolerance]) -> AirDateRange {
+    tolerances
+        .first()
+        .map_or(air_date, |&tolerance| air_date.widen(tolerance))
+}
+
+/// 别名匹配的条目放送日期在范围内才自动确认（没有日期时无法判断）
+fn confirms_alias(subject: &Subject, window: &AirDateRange) -> bool {
+    subject
+        .date
+        .as_deref()
+        .is_some_and(|date| window.contains_date(date))
+}
+
+/// 工具调用匹配选中的条目加入候选
+///
+/// 与搜索结果一样按条目类型与放送平台过滤，被过滤的条目标记原因，不参与匹配
+fn add_agent_candidate(item: &mut SeasonItem, anime: &AnimeNode, subject: Subject) {
+    if item.candidates.iter().any(|c| c.bgm_id == subject.id) {
+        return;
+    }
+    let mal_titles = title_match::mal_titles(anime);
+    let (kept, filtered) = candidate_filter::filter_candidates(item.mal.media_type, vec![subject]);
+    for subject in kept {
+        item.candidates
+            .push(BgmCandidate::scored(&subject, &mal_titles, None));
+    }
+    for (subject, reason) in filtered {
+        info!(
+            mal_id = item.mal.id,
+            bgm_id = subject.id,
+            reason = ?reason,
+            "工具调用匹配选中的条目被过滤，不采纳"
+        );
+        item.candidates.push(BgmCandidate {
+            filtered: Some(reason),
+            ..BgmCandidate::scored(&subject, &mal_titles, None)
+        });
     }
 }
 
+/// 将 LLM 的判断应用到未确认条目上（选中的 ID 必须在候选中）
+///
+/// 低置信度的选择不直接确认：保留候选并预选模型的建议，留给人工复核
+fn apply_model_decision(item: &mut SeasonItem, decision: MatchDecision) {
+    // 被过滤的候选（条目类型、放送平台不符）不采纳
+    let Some(matched) = decision
+        .id
+        .and_then(|bgm_id| {
+            item.candidates
+                .iter()
+                .find(|c| c.bgm_id == bgm_id && c.filtered.is_none())
+        })
+        .cloned()
+    else {
+        debug!(
+            mal_id = item.mal.id,
+            candidates_count = item.candidates.len(),
+            reason = ?decision.reason,
+            "未匹配，保留候选"
+        );
+        item.llm = Some(decision);
+        return;
+    };
+
+    if decision.is_low_confidence() {
+        info!(
+            mal_id = item.mal.id,
+            bgm_id = matched.bgm_id,
+            name = %matched.bgm_name,
+            reason = ?decision.reason,
+            "模型低置信度，预选后留给人工确认"
+        );
+        item.bgm_id = Some(matched.bgm_id);
+        item.bgm_name = Some(matched.bgm_name);
+        item.bgm_name_cn = matched.bgm_name_cn;
+        item.llm = Some(decision);
+        return;
+    }
+
+    info!(
+        mal_id = item.mal.id,
+        bgm_id = matched.bgm_id,
+        name = %matched.bgm_name,
+        model = %decision.judge.model,
+        confidence = ?decision.confidence,
+        "模型匹配"
+    );
+    *item = SeasonItem {
+        llm: Some(decision),
+        ..SeasonItem::matched(ConfirmStatus::Model, matched, item.mal.clone())
+    };
+}
+
 /// 季度处理器
 pub struct SeasonProcessor {
     mal_client: MalClient,
     bgm_client: BgmtvClient,
-    ds_client: Option<DsClient>,
+    llm_client: Option<LlmClient>,
+    llm_batch_size: usize,
+    consensus: Option<Consensus>,
+    consensus_stats: Mutex<ConsensusStats>,
+    agent: Option<MatchAgent>,
+    date_tolerances: Vec<DateTolerance>,
+    track_carry_over: bool,
+    relation_walk: bool,
 }
 
 impl SeasonProcessor {
@@ -247,32 +621,510 @@ impl SeasonProcessor {
         Self {
             mal_client,
             bgm_client,
-            ds_client: None,
+            llm_client: None,
+            llm_batch_size: 1,
+            consensus: None,
+            consensus_stats: Mutex::new(ConsensusStats::default()),
+            agent: None,
+            date_tolerances: DateTolerance::defaults(),
+            track_carry_over: false,
+            relation_walk: false,
         }
     }
 
-    /// 设置 DeepSeek 客户端（用于模型匹配验证）
-    pub fn with_ds_client(mut self, ds_client: DsClient) -> Self {
-        self.ds_client = Some(ds_client);
+    /// 设置 LLM 客户端（用于模型匹配验证）
+    pub fn with_llm_client(mut self, llm_client: LlmClient) -> Self {
+        self.llm_client = Some(llm_client);
         self
     }
 
-    /// 处理季度数据
+    /// LLM 客户端（用于读取本次运行的用量统计）
+    pub fn llm_client(&self) -> Option<&LlmClient> {
+        self.llm_client.as_ref()
+    }
+
+    /// 设置每次 LLM 请求打包的条目数（1 为逐条请求）
+    pub fn with_llm_batch_size(mut self, batch_size: usize) -> Self {
+        self.llm_batch_size = batch_size.max(1);
+        self
+    }
+
+    /// 启用双判定共识模式
+    pub fn with_consensus(mut self, consensus: Consensus) -> Self {
+        self.consensus = Some(consensus);
+        self
+    }
+
+    /// 共识模式配置（用于读取第二个模型的用量）
+    pub fn consensus(&self) -> Option<&Consensus> {
+        self.consensus.as_ref()
+    }
+
+    /// 启用工具调用匹配（模型判断无匹配或搜索无结果的条目，由模型自行搜索）
+    pub fn with_agent(mut self, agent: MatchAgent) -> Self {
+        self.agent = Some(agent);
+        self
+    }
+
+    /// 设置限制日期搜索逐级放宽的容差（为空时直接不限日期搜索）
+    pub fn with_date_tolerances(mut self, tolerances: Vec<DateTolerance>) -> Self {
+        self.date_tolerances = tolerances;
+        self
+    }
+
+    /// 记录续播番组（引用首次匹配所在季度的结果，写入 `carry_over`）
+    pub fn with_carry_over(mut self, track: bool) -> Self {
+        self.track_carry_over = track;
+        self
+    }
+
+    /// 是否由前作映射推断续作（默认关闭，需要额外的 MAL 与 Bangumi 请求）
+    pub fn with_relation_walk(mut self, enabled: bool) -> Self {
+        self.relation_walk = enabled;
+        self
+    }
+
+    /// 本次运行累计的共识统计
+    pub fn consensus_stats(&self) -> ConsensusStats {
+        *self.consensus_stats.lock().unwrap()
+    }
+
+    /// 使用 LLM 从候选中选出匹配条目
+    ///
+    /// 按 `llm_batch_size` 分批请求；整批响应无效或单条结果无效时，
+    /// 对应条目回退到逐条请求。启用共识模式时，确认前再独立判断一次
+    async fn resolve_with_llm(
+        &self,
+        llm: &LlmClient,
+        items: &mut [SeasonItem],
+        pending: &[&PendingMatch],
+    ) -> ConsensusStats {
+        let mut stats = ConsensusStats::default();
+        let mut reused = 0;
+
+        for (n, chunk) in pending.chunks(self.llm_batch_size).enumerate() {
+            if llm.budget_exhausted() {
+                warn!(
+                    remaining = pending.len() - n * self.llm_batch_size,
+                    "LLM 预算已用完，剩余条目保留候选"
+                );
+                break;
+            }
+
+            let candidates: Vec<_> = chunk.iter().map(|p| p.candidate_entries()).collect();
+            let mut decisions: Vec<Option<MatchDecision>> = vec![None; chunk.len()];
+
+            // 复用已保存的判断，只请求输入有变化的条目
+            let mut uncached = Vec::new();
+            for (k, p) in chunk.iter().enumerate() {
+                let mal = p.mal_entry(&items[p.index].mal);
+                match llm.cached_decision(&mal, &candidates[k]).await {
+                    Some(decision) => {
+                        debug!(mal_id = items[p.index].mal.id, "复用已保存的模型判断");
+                        decisions[k] = Some(decision);
+                        reused += 1;
+                    }
+                    None => uncached.push(k),
+                }
+            }
+
+            let mut fallback = Vec::new();
+            if uncached.len() == 1 {
+                fallback.push(uncached[0]);
+            } else if uncached.len() > 1 {
+                let batch: Vec<_> = uncached
+                    .iter()
+                    .map(|&k| BatchItem {
+                        mal: chunk[k].mal_entry(&items[chunk[k].index].mal),
+                        candidates: &candidates[k],
+                    })
+                    .collect();
+
+                match llm.match_anime_batch(&batch).await {
+                    Ok(results) => {
+                        for (&k, decision) in uncached.iter().zip(results) {
+                            match decision {
+                                Some(decision) => decisions[k] = Some(decision),
+                                None => fallback.push(k),
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        warn!(
+                            batch_size = uncached.len(),
+                            error = %e,
+                            "LLM 批量匹配失败，回退到逐条请求"
+                        );
+                        fallback.extend(&uncached);
+                    }
+                }
+            }
+
+            for k in fallback {
+                if llm.budget_exhausted() {
+                    break;
+                }
+                let p = &chunk[k];
+                let mal = p.mal_entry(&items[p.index].mal);
+                match llm.match_anime(&mal, &candidates[k]).await {
+                    Ok(decision) => decisions[k] = Some(decision),
+                    Err(e) => {
+                        warn!(
+                            mal_id = items[p.index].mal.id,
+                            error = %e,
+                            "LLM 匹配失败"
+                        );
+                    }
+                }
+            }
+
+            for &k in &uncached {
+                if let Some(decision) = &decisions[k] {
+                    let p = &chunk[k];
+                    let mal = p.mal_entry(&items[p.index].mal);
+                    llm.save_decision(&mal, &candidates[k], decision).await;
+                }
+            }
+
+            for (k, decision) in decisions.into_iter().enumerate() {
+                let Some(decision) = decision else {
+                    continue;
+                };
+                let p = &chunk[k];
+
+                // 只有会被确认的判断才需要第二次判断（只有一个候选时打乱顺序无意义，不计入共识）
+                let needs_consensus = decision.id.is_some() && !decision.is_low_confidence();
+                let Some(consensus) = self
+                    .consensus
+                    .as_ref()
+                    .filter(|c| needs_consensus && c.applies(&candidates[k]))
+                else {
+                    apply_model_decision(&mut items[p.index], decision);
+                    continue;
+                };
+
+                let mal = p.mal_entry(&items[p.index].mal);
+                let second = consensus.second_opinion(llm, &mal, &candidates[k]).await;
+                let item = &mut items[p.index];
+                match second {
+                    Ok(second) if consensus::agrees(&decision, &second) => {
+                        stats.agreed += 1;
+                        apply_model_decision(item, decision);
+                        item.llm_second = Some(second);
+                    }
+                    Ok(second) => {
+                        stats.disagreed += 1;
+                        info!(
+                            mal_id = item.mal.id,
+                            first = ?decision.id,
+                            second = ?second.id,
+                            "两次模型判断不一致，留给人工确认"
+                        );
+                        item.llm = Some(decision);
+                        item.llm_second = Some(second);
+                    }
+                    Err(e) => {
+                        stats.failed += 1;
+                        warn!(
+                            mal_id = item.mal.id,
+                            error = %e,
+                            "第二次模型判断失败，留给人工确认"
+                        );
+                        item.llm = Some(decision);
+                    }
+                }
+            }
+        }
+
+        if reused > 0 {
+            info!(count = reused, "复用已保存的模型判断");
+        }
+        self.consensus_stats.lock().unwrap().add(&stats);
+        stats
+    }
+
+    /// 使用工具调用匹配处理剩余条目，返回确认为 model 的数量
+    ///
+    /// 选中的条目加入候选后按普通模型判断处理（低置信度只预选）
+    async fn resolve_with_agent(
+        &self,
+        llm: &LlmClient,
+        agent: &MatchAgent,
+        items: &mut [SeasonItem],
+        targets: &[&PendingMatch],
+    ) -> usize {
+        let mut resolved = 0;
+
+        for (n, p) in targets.iter().enumerate() {
+            if llm.budget_exhausted() {
+                warn!(
+                    remaining = targets.len() - n,
+                    "LLM 预算已用完，跳过工具调用匹配"
+                );
+                break;
+            }
+
+            let mal = p.mal_entry(&items[p.index].mal);
+            let rejected = p.candidate_entries();
+            let result = agent.resolve(llm, &self.bgm_client, &mal, &rejected).await;
+            let item = &mut items[p.index];
+            match result {
+                Ok(AgentMatch { decision, subject }) => {
+                    info!(
+                        mal_id = item.mal.id,
+                        bgm_id = ?decision.id,
+                        searches = ?decision.searches,
+                        "工具调用匹配完成"
+                    );
+                    if let Some(subject) = subject {
+                        add_agent_candidate(item, &p.anime, subject);
+                    }
+                    apply_model_decision(item, decision);
+                    if item.status == ConfirmStatus::Model {
+                        resolved += 1;
+                    }
+                }
+                Err(e) => {
+                    warn!(mal_id = item.mal.id, error = %e, "工具调用匹配失败");
+                }
+            }
+        }
+
+        resolved
+    }
+
+    /// 搜索 Bangumi 并尝试完全匹配与别名匹配
+    ///
+    /// 在 `air_date`（MAL 开播日期或季度范围）前后按容差逐级放宽搜索，都没有结果时不限日期
+    async fn search_and_match(
+        &self,
+        anime: &AnimeNode,
+        mal_info: MalInfo,
+        air_date: AirDateRange,
+    ) -> SearchOutcome {
+        // 使用日文标题搜索 Bangumi
+        let search_keyword = mal_info.title_ja.as_deref().unwrap_or(&mal_info.title);
+        debug!(keyword = search_keyword, mal_id = mal_info.id, "搜索 Bangumi");
+
+        // 制作公司一致的候选排在前面；条目类型、放送平台不符的候选不参与匹配，系列条目排在最后。
+        // 每次搜索后先过滤，只有不符的条目时也继续放宽
+        let studios = mal_studios(anime);
+        let mut filtered = Vec::new();
+        let mut screen = |mut subjects: Vec<Subject>| {
+            studio::rank_by_studio(&studios, &mut subjects);
+            let (kept, removed) =
+                candidate_filter::filter_candidates(mal_info.media_type, subjects);
+            // 放宽后的搜索会再次返回之前过滤的条目
+            for (subject, reason) in removed {
+                if !filtered
+                    .iter()
+                    .any(|(s, _): &(Subject, _)| s.id == subject.id)
+                {
+                    filtered.push((subject, reason));
+                }
+            }
+            kept
+        };
+
+        // 先限制日期搜索，逐级放宽
+        let mut results = Vec::new();
+        for &tolerance in &self.date_tolerances {
+            let window = air_date.widen(tolerance);
+            let (start_date, end_date) = window.query_bounds();
+            match self
+                .bgm_client
+                .search_anime_by_keyword(search_keyword, &start_date, &end_date)
+                .await
+            {
+                Ok(r) => results = screen(r),
+                Err(e) => {
+                    warn!(
+                        mal_id = mal_info.id,
+                        title = %mal_info.title,
+                        error = %e,
+                        "Bangumi API 搜索失败"
+                    );
+                    return SearchOutcome::Done(SeasonItem::new(ConfirmStatus::Error, mal_info));
+                }
+            }
+            if !results.is_empty() {
+                break;
+            }
+            debug!(
+                keyword = search_keyword,
+                window = %window,
+                "限制日期搜索无可用结果，放宽日期范围"
+            );
+        }
+
+        // 如果没有结果，不限制日期再搜一次
+        let results = if results.is_empty() {
+            debug!(keyword = search_keyword, "限制日期搜索无结果，回退到无限制搜索");
+            match self
+                .bgm_client
+                .search_anime_by_keyword_no_date(search_keyword)
+                .await
+            {
+                Ok(r) => screen(r),
+                Err(e) => {
+                    warn!(
+                        mal_id = mal_info.id,
+                        title = %mal_info.title,
+                        error = %e,
+                        "Bangumi API 搜索失败（无日期限制）"
+                    );
+                    return SearchOutcome::Done(SeasonItem::new(ConfirmStatus::Error, mal_info));
+                }
+            }
+        } else {
+            results
+        };
+
+        if !filtered.is_empty() {
+            debug!(
+                mal_id = mal_info.id,
+                filtered = filtered.len(),
+                "过滤类型或平台不符的候选"
+            );
+        }
+        let mal_titles = title_match::mal_titles(anime);
+        let candidates: Vec<BgmCandidate> = results
+            .iter()
+            .map(|s| BgmCandidate::scored(s, &mal_titles, Some(search_keyword)))
+            .chain(filtered.iter().map(|(s, reason)| BgmCandidate {
+                filtered: Some(*reason),
+                ..BgmCandidate::scored(s, &mal_titles, Some(search_keyword))
+            }))
+            .collect();
+
+        // 严格匹配：日文标题完全相等
+        let exact_match = candidates
+            .iter()
+            .filter(|c| c.filtered.is_none())
+            .find(|c| Some(c.bgm_name.as_str()) == mal_info.title_ja.as_deref());
+
+        if let Some(matched) = exact_match {
+            info!(
+                mal_id = mal_info.id,
+                bgm_id = matched.bgm_id,
+                name = %matched.bgm_name,
+                "完全匹配"
+            );
+            return SearchOutcome::Done(SeasonItem::matched(
+                ConfirmStatus::Match,
+                matched.clone(),
+                mal_info,
+            ));
+        }
+
+        // 别名匹配：MAL 各标题与 Bangumi 名称、中文名、信息框别名规范化后一致
+        // 续作、重制常与前作同名，放送日期不在开播日期范围内时只预选，留给模型或人工确认
+        let mut preselected = None;
+        if let Some((index, alias)) = title_match::find_alias_match(anime, &results) {
+            let matched = candidates[index].clone();
+            let window = alias_window(air_date, &self.date_tolerances);
+            if confirms_alias(&results[index], &window) {
+                info!(
+                    mal_id = mal_info.id,
+                    bgm_id = matched.bgm_id,
+                    mal_title = %alias.mal_title,
+                    bgm_title = %alias.bgm_title,
+                    "别名匹配"
+                );
+                return SearchOutcome::Done(SeasonItem {
+                    alias_match: Some(alias),
+                    ..SeasonItem::matched(ConfirmStatus::Match, matched, mal_info)
+                });
+            }
+            info!(
+                mal_id = mal_info.id,
+                bgm_id = matched.bgm_id,
+                date = ?results[index].date,
+                window = %window,
+                "别名匹配但放送日期不在范围内，预选后留给模型判断"
+            );
+            preselected = Some((alias, matched));
+        }
+
+        if results.is_empty() {
+            warn!(
+                mal_id = mal_info.id,
+                title = %mal_info.title,
+                filtered = filtered.len(),
+                "未找到匹配"
+            );
+        }
+        let mut item = SeasonItem {
+            candidates,
+            ..SeasonItem::new(ConfirmStatus::Unconfirmed, mal_info)
+        };
+        if let Some((alias, matched)) = preselected {
+            item.bgm_id = Some(matched.bgm_id);
+            item.bgm_name = Some(matched.bgm_name);
+            item.bgm_name_cn = matched.bgm_name_cn;
+            item.alias_match = Some(alias);
+        }
+        SearchOutcome::Pending(item, results)
+    }
+
+    /// 使用 LLM 验证未确认的条目
+    ///
+    /// 有候选的条目由模型从候选中选择；模型判断无匹配或搜索无结果的条目，
+    /// 启用工具调用匹配时再由模型自行搜索。返回共识统计与工具调用匹配确认的数量
+    async fn resolve_pending(
+        &self,
+        items: &mut [SeasonItem],
+        pending: &[PendingMatch],
+    ) -> (ConsensusStats, usize) {
+        let Some(llm) = &self.llm_client else {
+            return (ConsensusStats::default(), 0);
+        };
+
+        let (with_candidates, without_candidates): (Vec<_>, Vec<_>) =
+            pending.iter().partition(|p| !p.subjects.is_empty());
+        let consensus_stats = self.resolve_with_llm(llm, items, &with_candidates).await;
+
+        let Some(agent) = &self.agent else {
+            return (consensus_stats, 0);
+        };
+        let targets: Vec<_> = with_candidates
+            .iter()
+            .filter(|p| {
+                let item = &items[p.index];
+                item.status == ConfirmStatus::Unconfirmed
+                    && item.llm.as_ref().is_some_and(|d| d.id.is_none())
+            })
+            .chain(&without_candidates)
+            .copied()
+            .collect();
+        let agent_resolved = self.resolve_with_agent(llm, agent, items, &targets).await;
+
+        (consensus_stats, agent_resolved)
+    }
+
+    /// 处理季度数据，读取并写入 `release_dir` 下的季度文件
+    ///
+    /// `history` 为运行开始时读取的发布数据，处理完成后记录本季度的结果
     pub async fn process(
         &self,
-        year: u32,
-        season: Season,
-        output_path: &Path,
+        season: YearSeason,
+        release_dir: &Path,
+        history: &mut SeasonHistory,
     ) -> Result<SeasonData, CoreError> {
+        let output_path = season.release_file(release_dir);
         // 尝试加载现有数据
-        let existing = SeasonData::load(output_path).await?;
+        let existing = SeasonData::load(&output_path).await?;
         let confirmed_ids = existing
             .as_ref()
             .map(|d| d.confirmed_mal_ids())
             .unwrap_or_default();
+        // 当前季度已列为条目的 MAL ID（判断没有开播季度与日期的条目是否为新番）
+        let current_ids: HashSet<u64> = existing
+            .as_ref()
+            .map(|d| d.items.iter().map(|i| i.mal.id).collect())
+            .unwrap_or_default();
 
         info!(
-            year = year,
             season = %season,
             confirmed_count = confirmed_ids.len(),
             "开始处理季度数据"
@@ -281,22 +1133,34 @@ impl SeasonProcessor {
         // 获取 MAL 季度列表
         let anime_list = self
             .mal_client
-            .get_all_seasonal_anime(year, season, true)
+            .get_all_seasonal_anime(season.year, season.season, true)
             .await?;
         info!(total = anime_list.len(), "从 MAL 获取番组列表");
 
-        let mut data = SeasonData::new(year, season);
-        let (start_date, end_date) = season_date_range(year, season);
+        let mut data = SeasonData::new(season);
+        let mut archive = SeasonArchive::new(release_dir);
+        // 等待 LLM 验证的条目
+        let mut pending = Vec::new();
 
         for anime in anime_list {
-            // 跳过续播番组（start_season 与当前季度不匹配）
-            let is_new = anime
-                .start_season
-                .as_ref()
-                .map(|s| s.year == year && s.season == season)
-                .unwrap_or(false);
-            if !is_new {
-                debug!(id = anime.id, title = %anime.title, "跳过续播番组");
+            // 续播番组（开播季度与当前季度不匹配）：不记录时跳过
+            let membership = membership::classify(&anime, season, history, &current_ids);
+            if membership == Membership::CarryOver {
+                // 没有开播季度时以首次收录的季度查找首次匹配
+                let start = membership::start_season(&anime)
+                    .or_else(|| history.reported_before(anime.id, season));
+                let is_earlier = start.is_none_or(|start| start < season);
+                if self.track_carry_over
+                    && is_earlier
+                    && let Some(mal_info) = MalInfo::from_anime_node(&anime)
+                    && !mal_info.media_type.should_skip()
+                {
+                    let item = archive.resolve(mal_info, start, season).await?;
+                    debug!(id = anime.id, source = ?item.source, "记录续播番组");
+                    data.carry_over.push(item);
+                } else {
+                    debug!(id = anime.id, title = %anime.title, "跳过续播番组");
+                }
                 continue;
             }
 
@@ -310,18 +1174,31 @@ impl SeasonProcessor {
             };
 
             // 如果已经确认（包括 skip），保留原有数据
-            if confirmed_ids.contains(&mal_info.id) {
-                if let Some(ref existing_data) = existing {
-                    if let Some(item) = existing_data
-                        .items
-                        .iter()
-                        .find(|i| i.mal.id == mal_info.id)
-                    {
-                        data.items.push(item.clone());
-                        debug!(mal_id = mal_info.id, "保留已确认条目");
-                        continue;
-                    }
-                }
+            if confirmed_ids.contains(&mal_info.id)
+                && let Some(ref existing_data) = existing
+                && let Some(item) = existing_data
+                    .items
+                    .iter()
+                    .find(|i| i.mal.id == mal_info.id)
+            {
+                data.items.push(item.clone());
+                debug!(mal_id = mal_info.id, "保留已确认条目");
+                continue;
+            }
+
+            // 无法判断季度的条目写入报告，不参与匹配
+            if membership == Membership::Unknown {
+                info!(
+                    mal_id = mal_info.id,
+                    title = %mal_info.title,
+                    start_date = ?anime.start_date,
+                    "无法判断开播季度，写入 unclassified"
+                );
+                data.unclassified.push(UnclassifiedItem {
+                    mal: mal_info,
+                    start_date: anime.start_date.clone(),
+                });
+                continue;
             }
 
             // 如果是 special/tv_special/music/pv 类型，标记为 skip
@@ -332,193 +1209,40 @@ impl SeasonProcessor {
                     media_type = ?mal_info.media_type,
                     "标记为 skip"
                 );
-                data.items.push(SeasonItem {
-                    status: ConfirmStatus::Skip,
-                    bgm_id: None,
-                    bgm_name: None,
-                    bgm_name_cn: None,
-                    candidates: vec![],
-                    mal: mal_info,
-                });
+                data.items
+                    .push(SeasonItem::new(ConfirmStatus::Skip, mal_info));
                 continue;
             }
 
-            // 使用日文标题搜索 Bangumi
-            let search_keyword = mal_info.title_ja.as_deref().unwrap_or(&mal_info.title);
-            debug!(keyword = search_keyword, mal_id = mal_info.id, "搜索 Bangumi");
-
-            // 先限制日期搜索
-            let results = match self
-                .bgm_client
-                .search_anime_by_keyword(search_keyword, &start_date, &end_date)
+            match self
+                .search_and_match(&anime, mal_info, search_air_date(&anime, season))
                 .await
             {
-                Ok(r) => r,
-                Err(e) => {
-                    warn!(
-                        mal_id = mal_info.id,
-                        title = %mal_info.title,
-                        error = %e,
-                        "Bangumi API 搜索失败"
-                    );
-                    data.items.push(SeasonItem {
-                        status: ConfirmStatus::Error,
-                        bgm_id: None,
-                        bgm_name: None,
-                        bgm_name_cn: None,
-                        candidates: vec![],
-                        mal: mal_info,
+                SearchOutcome::Done(item) => data.items.push(item),
+                SearchOutcome::Pending(item, subjects) => {
+                    // 留给 LLM 验证（全部搜索完成后统一批量请求）
+                    pending.push(PendingMatch {
+                        index: data.items.len(),
+                        anime: anime.clone(),
+                        subjects,
                     });
-                    continue;
-                }
-            };
-
-            // 如果没有结果，不限制日期再搜一次
-            let results = if results.is_empty() {
-                debug!(keyword = search_keyword, "限制日期搜索无结果，回退到无限制搜索");
-                match self
-                    .bgm_client
-                    .search_anime_by_keyword_no_date(search_keyword)
-                    .await
-                {
-                    Ok(r) => r,
-                    Err(e) => {
-                        warn!(
-                            mal_id = mal_info.id,
-                            title = %mal_info.title,
-                            error = %e,
-                            "Bangumi API 搜索失败（无日期限制）"
-                        );
-                        data.items.push(SeasonItem {
-                            status: ConfirmStatus::Error,
-                            bgm_id: None,
-                            bgm_name: None,
-                            bgm_name_cn: None,
-                            candidates: vec![],
-                            mal: mal_info,
-                        });
-                        continue;
-                    }
-                }
-            } else {
-                results
-            };
-
-            let candidates: Vec<_> = results
-                .iter()
-                .map(|s| BgmCandidate {
-                    bgm_id: s.id,
-                    bgm_name: s.name.clone().unwrap_or_default(),
-                    bgm_name_cn: s.name_cn.clone(),
-                })
-                .collect();
-
-            // 严格匹配：日文标题完全相等
-            let exact_match = candidates
-                .iter()
-                .find(|c| Some(c.bgm_name.as_str()) == mal_info.title_ja.as_deref());
-
-            let item = if let Some(matched) = exact_match {
-                info!(
-                    mal_id = mal_info.id,
-                    bgm_id = matched.bgm_id,
-                    name = %matched.bgm_name,
-                    "完全匹配"
-                );
-                SeasonItem {
-                    status: ConfirmStatus::Match,
-                    bgm_id: Some(matched.bgm_id),
-                    bgm_name: Some(matched.bgm_name.clone()),
-                    bgm_name_cn: matched.bgm_name_cn.clone(),
-                    candidates: vec![],
-                    mal: mal_info,
-                }
-            } else if !candidates.is_empty() {
-                // 使用 LLM 验证匹配
-                let model_match = if let Some(ref ds) = self.ds_client {
-                    let candidate_tuples: Vec<_> = candidates
-                        .iter()
-                        .map(|c| {
-                            (
-                                c.bgm_id,
-                                c.bgm_name.as_str(),
-                                c.bgm_name_cn.as_deref(),
-                            )
-                        })
-                        .collect();
-
-                    match ds
-                        .match_anime(
-                            &mal_info.title,
-                            mal_info.title_ja.as_deref(),
-                            &candidate_tuples,
-                        )
-                        .await
-                    {
-                        Ok(Some(bgm_id)) => {
-                            candidates.iter().find(|c| c.bgm_id == bgm_id).cloned()
-                        }
-                        Ok(None) => None,
-                        Err(e) => {
-                            warn!(
-                                mal_id = mal_info.id,
-                                error = %e,
-                                "LLM 匹配失败"
-                            );
-                            None
-                        }
-                    }
-                } else {
-                    None
-                };
-
-                if let Some(matched) = model_match {
-                    info!(
-                        mal_id = mal_info.id,
-                        bgm_id = matched.bgm_id,
-                        name = %matched.bgm_name,
-                        "模型匹配"
-                    );
-                    SeasonItem {
-                        status: ConfirmStatus::Model,
-                        bgm_id: Some(matched.bgm_id),
-                        bgm_name: Some(matched.bgm_name),
-                        bgm_name_cn: matched.bgm_name_cn,
-                        candidates: vec![],
-                        mal: mal_info,
-                    }
-                } else {
-                    debug!(
-                        mal_id = mal_info.id,
-                        candidates_count = candidates.len(),
-                        "未匹配，保留候选"
-                    );
-                    SeasonItem {
-                        status: ConfirmStatus::Unconfirmed,
-                        bgm_id: None,
-                        bgm_name: None,
-                        bgm_name_cn: None,
-                        candidates,
-                        mal: mal_info,
-                    }
-                }
-            } else {
-                warn!(
-                    mal_id = mal_info.id,
-                    title = %mal_info.title,
-                    "未找到匹配"
-                );
-                SeasonItem {
-                    status: ConfirmStatus::Unconfirmed,
-                    bgm_id: None,
-                    bgm_name: None,
-                    bgm_name_cn: None,
-                    candidates: vec![],
-                    mal: mal_info,
+                    data.items.push(item);
                 }
-            };
+            }
+        }
 
-            data.items.push(item);
+        let (consensus_stats, agent_resolved) =
+            self.resolve_pending(&mut data.items, &pending).await;
+
+        // 仍没有结果的条目由前作映射推断续作
+        if self.relation_walk && data.items.iter().any(relation::is_relation_target) {
+            let mappings = ConfirmedMappings::load(release_dir).await?;
+            let proposed = self
+                .resolve_with_relations(season, &mut data.items, &mappings)
+                .await;
+            if proposed > 0 {
+                info!(count = proposed, "由前作推断续作");
+            }
         }
 
         // 统计结果
@@ -560,12 +1284,27 @@ impl SeasonProcessor {
             unconfirmed = unconfirmed_count,
             error = error_count,
             skip = skip_count,
+            unclassified = data.unclassified.len(),
+            carry_over = data.carry_over.len(),
             "处理完成"
         );
+        if self.consensus.is_some() {
+            info!(
+                agreed = consensus_stats.agreed,
+                disagreed = consensus_stats.disagreed,
+                failed = consensus_stats.failed,
+                agreement_rate = ?consensus_stats.agreement_rate(),
+                "模型共识统计"
+            );
+        }
+        if self.agent.is_some() {
+            info!(resolved = agent_resolved, "工具调用匹配确认");
+        }
 
         // 保存结果
-        data.save(output_path).await?;
+        data.save(&output_path).await?;
         info!(path = %output_path.display(), "已保存到文件");
+        history.record(&data);
 
         Ok(data)
     }
@@ -574,6 +1313,8 @@ impl SeasonProcessor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::llm::{Confidence, LlmJudge};
+    use crate::services::mal::Season;
 
     #[test]
     fn test_rating_from_mal() {
@@ -605,6 +1346,13 @@ mod tests {
         // 未知类型返回 None
         assert_eq!(MediaType::from_mal(Some("unknown")), None);
         assert_eq!(MediaType::from_mal(None), None);
+
+        for media_type in [MediaType::Tv, MediaType::TvSpecial, MediaType::Pv] {
+            assert_eq!(
+                MediaType::from_mal(Some(media_type.as_mal_str())),
+                Some(media_type)
+            );
+        }
     }
 
     #[test]
@@ -621,25 +1369,6 @@ mod tests {
         assert!(MediaType::Pv.should_skip());
     }
 
-    #[test]
-    fn test_season_date_range() {
-        let (start, end) = season_date_range(2026, Season::Winter);
-        assert_eq!(start, "2025-12-01");
-        assert_eq!(end, "2026-03-31");
-
-        let (start, end) = season_date_range(2026, Season::Spring);
-        assert_eq!(start, "2026-03-01");
-        assert_eq!(end, "2026-06-30");
-
-        let (start, end) = season_date_range(2026, Season::Summer);
-        assert_eq!(start, "2026-06-01");
-        assert_eq!(end, "2026-09-30");
-
-        let (start, end) = season_date_range(2026, Season::Fall);
-        assert_eq!(start, "2026-09-01");
-        assert_eq!(end, "2026-12-31");
-    }
-
     #[test]
     fn test_confirm_status() {
         assert!(!ConfirmStatus::Unconfirmed.is_confirmed());
@@ -674,14 +1403,19 @@ mod tests {
             serde_json::to_string(&ConfirmStatus::Skip).unwrap(),
             "\"skip\""
         );
+
+        assert_eq!("human".parse::<ConfirmStatus>(), Ok(ConfirmStatus::Human));
+        assert!("confirmed".parse::<ConfirmStatus>().is_err());
     }
 
     #[test]
     fn test_season_data_serialization() {
         let mut data = SeasonData {
-            season: "2026-winter".to_string(),
+            season: YearSeason::new(2026, Season::Winter),
             update_time: "2026-01-22T10:36:29+08:00".to_string(),
             items: vec![],
+            unclassified: vec![],
+            carry_over: vec![],
         };
 
         data.items.push(SeasonItem {
@@ -690,10 +1424,16 @@ mod tests {
             bgm_name: Some("葬送のフリーレン 第2期".to_string()),
             bgm_name_cn: Some("葬送的芙莉莲 第二季".to_string()),
             candidates: vec![],
+            llm: None,
+            llm_second: None,
+            relation: None,
+            alias_match: None,
             mal: MalInfo {
                 id: 59978,
                 title: "Sousou no Frieren 2nd Season".to_string(),
                 title_ja: Some("葬送のフリーレン 第2期".to_string()),
+                title_en: None,
+                synonyms: vec![],
                 media_type: MediaType::Tv,
                 rating: Rating::General,
             },
@@ -706,12 +1446,188 @@ mod tests {
 
         // 反序列化测试
         let parsed: SeasonData = serde_json::from_str(&json).unwrap();
-        assert_eq!(parsed.season, "2026-winter");
+        assert_eq!(parsed.season, YearSeason::new(2026, Season::Winter));
         assert_eq!(parsed.items.len(), 1);
         assert_eq!(parsed.items[0].bgm_id, Some(400602));
         assert_eq!(parsed.items[0].status, ConfirmStatus::Match);
     }
 
+    #[test]
+    fn test_candidate_from_subject() {
+        let subject: Subject = serde_json::from_value(serde_json::json!({
+            "id": 436738,
+            "type": 2,
+            "name": "劇場総集編ぼっち・ざ・ろっく！ Re:",
+            "name_cn": "",
+            "date": "2024-06-07",
+            "platform": "剧场版",
+            "eps": 0,
+            "total_episodes": 1,
+            "nsfw": false,
+            "images": { "small": "https://lain.bgm.tv/r/200/pic/cover/l/a.jpg", "grid": "" }
+        }))
+        .unwrap();
+        let candidate = BgmCandidate::scored(
+            &subject,
+            &["ぼっち・ざ・ろっく！劇場総集編"],
+            Some("ぼっち"),
+        );
+        assert_eq!(candidate.date.as_deref(), Some("2024-06-07"));
+        assert_eq!(candidate.platform.as_deref(), Some("剧场版"));
+        assert_eq!(candidate.eps, Some(1));
+        assert_eq!(candidate.nsfw, Some(false));
+        assert_eq!(
+            candidate.thumbnail.as_deref(),
+            Some("https://lain.bgm.tv/r/200/pic/cover/l/a.jpg")
+        );
+        assert!(candidate.score.is_some_and(|s| s > 0.5 && s < 1.0));
+        assert_eq!(candidate.keyword.as_deref(), Some("ぼっち"));
+
+        // 旧数据只有 ID 与名称
+        let old: BgmCandidate =
+            serde_json::from_str(r#"{"bgm_id": 1, "bgm_name": "a", "bgm_name_cn": "甲"}"#).unwrap();
+        assert_eq!(old.date, None);
+        assert_eq!(old.score, None);
+        let json = serde_json::to_value(&old).unwrap();
+        assert!(json.get("keyword").is_none());
+    }
+
+    fn unconfirmed_item_with_candidates() -> SeasonItem {
+        let mal = MalInfo {
+            id: 55357,
+            title: "Bocchi the Rock! Movie".to_string(),
+            title_ja: Some("ぼっち・ざ・ろっく！劇場総集編".to_string()),
+            title_en: None,
+            synonyms: vec![],
+            media_type: MediaType::Movie,
+            rating: Rating::General,
+        };
+        SeasonItem {
+            candidates: vec![
+                BgmCandidate {
+                    bgm_id: 436738,
+                    bgm_name: "劇場総集編ぼっち・ざ・ろっく！ Re:".to_string(),
+                    bgm_name_cn: None,
+                    ..Default::default()
+                },
+                BgmCandidate {
+                    bgm_id: 328609,
+                    bgm_name: "ぼっち・ざ・ろっく！".to_string(),
+                    bgm_name_cn: Some("孤独摇滚！".to_string()),
+                    ..Default::default()
+                },
+            ],
+            ..SeasonItem::new(ConfirmStatus::Unconfirmed, mal)
+        }
+    }
+
+    fn decision(id: Option<u64>, confidence: Option<Confidence>) -> MatchDecision {
+        MatchDecision {
+            id,
+            confidence,
+            reason: Some("剧场总集篇".to_string()),
+            searches: vec![],
+            judge: LlmJudge {
+                provider: "deepseek".to_string(),
+                model: "deepseek-chat".to_string(),
+                prompt_version: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_apply_model_decision() {
+        // 高置信度：确认为 model，清空候选并记录判断
+        let mut item = unconfirmed_item_with_candidates();
+        apply_model_decision(&mut item, decision(Some(436738), Some(Confidence::High)));
+        assert_eq!(item.status, ConfirmStatus::Model);
+        assert_eq!(item.bgm_id, Some(436738));
+        assert!(item.candidates.is_empty());
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(json["llm"]["provider"], "deepseek");
+        assert_eq!(json["llm"]["model"], "deepseek-chat");
+        assert_eq!(json["llm"]["reason"], "剧场总集篇");
+
+        // 低置信度：保持未确认，预选建议并保留候选
+        let mut item = unconfirmed_item_with_candidates();
+        apply_model_decision(&mut item, decision(Some(328609), Some(Confidence::Low)));
+        assert_eq!(item.status, ConfirmStatus::Unconfirmed);
+        assert_eq!(item.bgm_id, Some(328609));
+        assert_eq!(item.bgm_name_cn.as_deref(), Some("孤独摇滚！"));
+        assert_eq!(item.candidates.len(), 2);
+        assert!(item.llm.as_ref().unwrap().is_low_confidence());
+
+        // 无匹配：只记录判断
+        let mut item = unconfirmed_item_with_candidates();
+        apply_model_decision(&mut item, decision(None, Some(Confidence::High)));
+        assert_eq!(item.status, ConfirmStatus::Unconfirmed);
+        assert!(item.bgm_id.is_none());
+        assert!(item.llm.is_some());
+
+        // 选中的 ID 不在候选中：不采纳
+        let mut item = unconfirmed_item_with_candidates();
+        apply_model_decision(&mut item, decision(Some(1), Some(Confidence::High)));
+        assert_eq!(item.status, ConfirmStatus::Unconfirmed);
+        assert!(item.bgm_id.is_none());
+    }
+
+    #[test]
+    fn test_filtered_pick_stays_unconfirmed() {
+        // 模型选中被过滤的候选：不采纳，也不预选
+        let mut item = unconfirmed_item_with_candidates();
+        item.candidates[1].filtered = Some(FilterReason::Platform);
+        apply_model_decision(&mut item, decision(Some(328609), Some(Confidence::High)));
+        assert_eq!(item.status, ConfirmStatus::Unconfirmed);
+        assert!(item.bgm_id.is_none());
+        assert!(item.llm.is_some());
+        apply_model_decision(&mut item, decision(Some(328609), Some(Confidence::Low)));
+        assert!(item.bgm_id.is_none());
+
+        // 工具调用匹配选中平台不符的条目（MAL 剧场版对应 Bangumi TV 条目）：加入候选但标记过滤
+        let mut item = unconfirmed_item_with_candidates();
+        let anime: AnimeNode = serde_json::from_value(serde_json::json!({
+            "id": 55357,
+            "title": "Bocchi the Rock! Movie",
+            "media_type": "movie"
+        }))
+        .unwrap();
+        let tv: Subject = serde_json::from_value(serde_json::json!({
+            "id": 400000, "type": 2, "name": "ぼっち・ざ・ろっく！", "platform": "TV"
+        }))
+        .unwrap();
+        add_agent_candidate(&mut item, &anime, tv);
+        assert_eq!(item.candidates[2].bgm_id, 400000);
+        assert_eq!(item.candidates[2].filtered, Some(FilterReason::Platform));
+        apply_model_decision(&mut item, decision(Some(400000), Some(Confidence::High)));
+        assert_eq!(item.status, ConfirmStatus::Unconfirmed);
+        assert!(item.bgm_id.is_none());
+
+        // 平台相符的条目照常采纳
+        let movie: Subject = serde_json::from_value(serde_json::json!({
+            "id": 400001, "type": 2, "name": "劇場版", "platform": "剧场版"
+        }))
+        .unwrap();
+        add_agent_candidate(&mut item, &anime, movie);
+        assert_eq!(item.candidates[3].filtered, None);
+        apply_model_decision(&mut item, decision(Some(400001), Some(Confidence::High)));
+        assert_eq!(item.status, ConfirmStatus::Model);
+        assert_eq!(item.bgm_id, Some(400001));
+    }
+
+    #[test]
+    fn test_season_item_without_llm_field() {
+        // 旧数据没有 llm 字段，也能正常加载
+        let old = r#"{
+            "status": "model",
+            "bgm_id": 436738,
+            "bgm_name": "劇場総集編ぼっち・ざ・ろっく！ Re:",
+            "mal": {"id": 55357, "title": "Bocchi the Rock! Movie", "media_type": "movie", "rating": "general"}
+        }"#;
+        let parsed: SeasonItem = serde_json::from_str(old).unwrap();
+        assert!(parsed.llm.is_none());
+        assert!(serde_json::to_value(&parsed).unwrap().get("llm").is_none());
+    }
+
     #[test]
     fn test_mal_info_from_anime_node() {
         use crate::services::mal::{AlternativeTitles, AnimeNode};
@@ -742,8 +1658,30 @@ mod tests {
         assert_eq!(info.id, 59978);
         assert_eq!(info.title, "Sousou no Frieren 2nd Season");
         assert_eq!(info.title_ja, Some("葬送のフリーレン 第2期".to_string()));
+        assert_eq!(info.title_en, Some("Frieren Season 2".to_string()));
         assert_eq!(info.media_type, MediaType::Tv);
         assert_eq!(info.rating, Rating::General);
+        assert_eq!(
+            info.titles(),
+            vec![
+                "Sousou no Frieren 2nd Season",
+                "葬送のフリーレン 第2期",
+                "Frieren Season 2"
+            ]
+        );
+
+        // 旧数据没有英文标题与同义名
+        let old: MalInfo = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "title": "anime",
+            "media_type": "tv",
+            "rating": "general"
+        }))
+        .unwrap();
+        assert_eq!(old.titles(), vec!["anime"]);
+        let json = serde_json::to_value(&old).unwrap();
+        assert!(json.get("title_en").is_none());
+        assert!(json.get("synonyms").is_none());
     }
 
     #[test]
@@ -817,4 +1755,97 @@ mod tests {
         assert_eq!(info.media_type, MediaType::Special);
         assert!(info.media_type.should_skip());
     }
+
+    #[test]
+    fn test_alias_match_from_prior_season() {
+        let anime: AnimeNode = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "title": "Kaguya-sama wa Kokurasetai",
+            "start_date": "2024-04-06"
+        }))
+        .unwrap();
+        let subject = |id: u64, date: Option<&str>| -> Subject {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "type": 2,
+                "name": "かぐや様は告らせたい",
+                "date": date,
+                "infobox": [{ "key": "别名", "value": [{ "v": "Kaguya-sama wa Kokurasetai" }] }]
+            }))
+            .unwrap()
+        };
+        let air_date = AirDateRange::from_mal_date("2024-04-06").unwrap();
+        let window = alias_window(air_date, &DateTolerance::defaults());
+
+        // 前一年的同名条目别名一致，但放送日期不在范围内，不自动确认
+        let prior = [subject(10, Some("2023-04-08"))];
+        assert!(title_match::find_alias_match(&anime, &prior).is_some());
+        assert!(!confirms_alias(&prior[0], &window));
+
+        assert!(confirms_alias(&subject(11, Some("2024-04-07")), &window));
+        assert!(!confirms_alias(&subject(12, None), &window));
+        // 没有容差时只看开播日期本身
+        assert_eq!(alias_window(air_date, &[]), air_date);
+    }
+
+    #[tokio::test]
+    async fn test_search_widens_past_filtered_results() {
+        use crate::services::bgmtv::{self, SearchFilter};
+        use crate::services::cache::{CacheMode, ResponseCache};
+
+        let dir = std::env::temp_dir().join(format!("season-data-search-{}", std::process::id()));
+        let record = ResponseCache::new(&dir, CacheMode::Record);
+        let keyword = "テストアニメ";
+        let air_date = AirDateRange::from_mal_date("2024-04-06").unwrap();
+        let filter = |tolerance| {
+            let (start, end) = air_date.widen(tolerance).query_bounds();
+            SearchFilter::anime()
+                .air_date_range(&start, &end)
+                .include_nsfw()
+        };
+        let movie = serde_json::json!({
+            "id": 1, "type": 2, "name": keyword, "platform": "剧场版", "date": "2024-04-12"
+        });
+        let tv = serde_json::json!({
+            "id": 2, "type": 2, "name": keyword, "platform": "TV", "date": "2024-05-20"
+        });
+        // ±2 周只有同名剧场版（平台不符），±3 个月才有 TV 版
+        bgmtv::record_search(
+            &record,
+            keyword,
+            filter(DateTolerance::Days(14)),
+            serde_json::json!([movie]),
+        )
+        .await;
+        bgmtv::record_search(
+            &record,
+            keyword,
+            filter(DateTolerance::Months(3)),
+            serde_json::json!([movie, tv]),
+        )
+        .await;
+
+        let processor = SeasonProcessor::new(
+            MalClient::new(String::new()),
+            BgmtvClient::anonymous().with_cache(ResponseCache::new(&dir, CacheMode::Replay)),
+        );
+        let anime: AnimeNode = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "title": "Test Anime",
+            "media_type": "tv",
+            "alternative_titles": { "ja": keyword }
+        }))
+        .unwrap();
+        let mal_info = MalInfo::from_anime_node(&anime).unwrap();
+
+        let SearchOutcome::Done(item) =
+            processor.search_and_match(&anime, mal_info, air_date).await
+        else {
+            panic!("应放宽日期范围后完全匹配");
+        };
+        assert_eq!(item.status, ConfirmStatus::Match);
+        assert_eq!(item.bgm_id, Some(2));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
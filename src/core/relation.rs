@@ -0,0 +1,275 @@
+use super::eval::season_files;
+use super::{
+    AirDateRange, BgmCandidate, ConfirmStatus, CoreError, SeasonData, SeasonItem, SeasonProcessor,
+    YearSeason,
+};
+use crate::services::bgmtv::{RELATION_SEQUEL, Subject, SubjectType};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use tracing::{debug, info};
+
+/// MAL 关联作品中的前作
+const MAL_PREQUEL: &str = "prequel";
+/// 从前作出发最多沿续集走的步数
+const MAX_RELATION_HOPS: usize = 4;
+
+/// 沿 Bangumi 关联条目走的一步
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelationStep {
+    pub bgm_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bgm_name: Option<String>,
+    /// 与上一步的关系（`续集`）
+    pub relation: String,
+    /// 放送开始日期
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+
+/// 由前作映射推断续作的过程
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelationMatch {
+    /// MAL 关联作品中的前作
+    pub prequel_mal_id: u64,
+    /// 前作已确认映射所在的季度
+    pub prequel_season: YearSeason,
+    /// 前作已确认的 Bangumi 条目
+    pub prequel_bgm_id: u64,
+    /// 从前作出发沿续集走到的条目，最后一个为推断结果
+    pub chain: Vec<RelationStep>,
+}
+
+/// 发布数据中已确认的映射（MAL ID → 季度与 Bangumi ID）
+#[derive(Debug, Default)]
+pub(crate) struct ConfirmedMappings(HashMap<u64, (YearSeason, u64)>);
+
+impl ConfirmedMappings {
+    /// 读取发布目录中所有季度文件的已确认映射
+    pub(crate) async fn load(release_dir: &Path) -> Result<Self, CoreError> {
+        let mut mappings = HashMap::new();
+        if !release_dir.exists() {
+            return Ok(Self(mappings));
+        }
+        for path in season_files(release_dir).await? {
+            let Some(data) = SeasonData::load(&path).await? else {
+                continue;
+            };
+            for item in data.items.iter().filter(|i| i.status.is_confirmed()) {
+                if let Some(bgm_id) = item.bgm_id {
+                    mappings.insert(item.mal.id, (data.season, bgm_id));
+                }
+            }
+        }
+        Ok(Self(mappings))
+    }
+
+    fn get(&self, mal_id: u64) -> Option<(YearSeason, u64)> {
+        self.0.get(&mal_id).copied()
+    }
+}
+
+/// 条目的放送日期是否在季度范围内
+///
+/// 只有年份的日期（范围为整年）无法判断季度，至少需要年月
+fn fits_season(date: Option<&str>, window: &AirDateRange) -> bool {
+    date.filter(|d| d.trim().split('-').count() >= 2)
+        .and_then(AirDateRange::from_mal_date)
+        .is_some_and(|range| range.start >= window.start && range.start <= window.end)
+}
+
+/// 条目的放送日期是否早于季度（没有日期时无法判断，也继续往后走）
+fn before_season(date: Option<&str>, window: &AirDateRange) -> bool {
+    date.and_then(AirDateRange::from_mal_date)
+        .is_none_or(|range| range.start < window.start)
+}
+
+impl SeasonProcessor {
+    /// 从前作的 Bangumi 条目出发沿续集查找放送日期在季度内的条目
+    ///
+    /// 早于季度的续集继续往后走，最多 `MAX_RELATION_HOPS` 步
+    async fn walk_sequels(
+        &self,
+        prequel_bgm_id: u64,
+        window: &AirDateRange,
+    ) -> Option<(Vec<RelationStep>, Subject)> {
+        let mut visited = HashSet::from([prequel_bgm_id]);
+        let mut queue = VecDeque::from([(prequel_bgm_id, Vec::new())]);
+
+        while let Some((bgm_id, chain)) = queue.pop_front() {
+            if chain.len() >= MAX_RELATION_HOPS {
+                continue;
+            }
+            let related = match self.bgm_client.get_related_subjects(bgm_id).await {
+                Ok(related) => related,
+                Err(e) => {
+                    debug!(bgm_id = bgm_id, error = %e, "获取 Bangumi 关联条目失败");
+                    continue;
+                }
+            };
+
+            for sequel in related.into_iter().filter(|r| {
+                r.relation == RELATION_SEQUEL && r.subject_type == u8::from(SubjectType::Anime)
+            }) {
+                if !visited.insert(sequel.id) {
+                    continue;
+                }
+                // 关联条目不含放送日期，需要获取详情
+                let subject = match self.bgm_client.get_subject(sequel.id).await {
+                    Ok(subject) => subject,
+                    Err(e) => {
+                        debug!(bgm_id = sequel.id, error = %e, "获取 Bangumi 条目详情失败");
+                        continue;
+                    }
+                };
+                let mut chain = chain.clone();
+                chain.push(RelationStep {
+                    bgm_id: subject.id,
+                    bgm_name: subject.name.clone(),
+                    relation: sequel.relation,
+                    date: subject.date.clone(),
+                });
+
+                if fits_season(subject.date.as_deref(), window) {
+                    return Some((chain, subject));
+                }
+                if before_season(subject.date.as_deref(), window) {
+                    queue.push_back((subject.id, chain));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// 续作推断：对没有结果的未确认条目，由 MAL 前作在发布数据中的映射沿 Bangumi 续集查找
+    ///
+    /// 找到的条目加入候选并预选（不确认），推断过程记录在 `relation`。返回预选的数量
+    pub(super) async fn resolve_with_relations(
+        &self,
+        season: YearSeason,
+        items: &mut [SeasonItem],
+        mappings: &ConfirmedMappings,
+    ) -> usize {
+        let window = AirDateRange::season(season);
+        let mut proposed = 0;
+
+        for item in items.iter_mut().filter(|i| is_relation_target(i)) {
+            let related = match self.mal_client.get_related_anime(item.mal.id).await {
+                Ok(related) => related,
+                Err(e) => {
+                    debug!(mal_id = item.mal.id, error = %e, "获取 MAL 关联作品失败");
+                    continue;
+                }
+            };
+
+            let prequels = related
+                .iter()
+                .filter(|r| r.relation_type == MAL_PREQUEL)
+                .filter_map(|r| Some((r.node.id, mappings.get(r.node.id)?)));
+            for (prequel_mal_id, (prequel_season, prequel_bgm_id)) in prequels {
+                let Some((chain, subject)) = self.walk_sequels(prequel_bgm_id, &window).await
+                else {
+                    continue;
+                };
+                info!(
+                    mal_id = item.mal.id,
+                    prequel_mal_id = prequel_mal_id,
+                    prequel_bgm_id = prequel_bgm_id,
+                    bgm_id = subject.id,
+                    hops = chain.len(),
+                    "由前作推断续作，预选后留给人工确认"
+                );
+                if !item.candidates.iter().any(|c| c.bgm_id == subject.id) {
+                    item.candidates
+                        .push(BgmCandidate::scored(&subject, &item.mal.titles(), None));
+                }
+                item.bgm_id = Some(subject.id);
+                item.bgm_name = subject.name.clone();
+                item.bgm_name_cn = subject.name_cn.clone();
+                item.relation = Some(RelationMatch {
+                    prequel_mal_id,
+                    prequel_season,
+                    prequel_bgm_id,
+                    chain,
+                });
+                proposed += 1;
+                break;
+            }
+        }
+
+        proposed
+    }
+}
+
+/// 未确认且没有任何预选的条目
+pub(super) fn is_relation_target(item: &SeasonItem) -> bool {
+    item.status == ConfirmStatus::Unconfirmed && item.bgm_id.is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_util::mal;
+    use crate::services::mal::Season;
+
+    #[test]
+    fn test_season_window() {
+        let window = AirDateRange::season(YearSeason::new(2026, Season::Winter));
+        assert!(fits_season(Some("2026-01-16"), &window));
+        // 季度范围向前多包含一个月
+        assert!(fits_season(Some("2025-12-20"), &window));
+        assert!(!fits_season(Some("2026-04-03"), &window));
+        assert!(!fits_season(None, &window));
+        // 只有年份时不能确定季度，继续往后走
+        assert!(!fits_season(Some("2026"), &window));
+        assert!(fits_season(Some("2026-01"), &window));
+        assert!(!before_season(Some("2026"), &window));
+
+        assert!(before_season(Some("2023-09-29"), &window));
+        assert!(before_season(None, &window));
+        assert!(!before_season(Some("2026-01-16"), &window));
+        assert!(!before_season(Some("2026-07-01"), &window));
+    }
+
+    #[test]
+    fn test_relation_target() {
+        let mal = mal(1);
+        assert!(is_relation_target(&SeasonItem::new(
+            ConfirmStatus::Unconfirmed,
+            mal.clone()
+        )));
+        // 模型低置信度预选的条目不覆盖
+        let preselected = SeasonItem {
+            bgm_id: Some(10),
+            ..SeasonItem::new(ConfirmStatus::Unconfirmed, mal.clone())
+        };
+        assert!(!is_relation_target(&preselected));
+        assert!(!is_relation_target(&SeasonItem::new(
+            ConfirmStatus::Error,
+            mal
+        )));
+    }
+
+    #[test]
+    fn test_relation_match_serialization() {
+        let relation = RelationMatch {
+            prequel_mal_id: 52991,
+            prequel_season: YearSeason::new(2023, Season::Fall),
+            prequel_bgm_id: 400602,
+            chain: vec![RelationStep {
+                bgm_id: 515759,
+                bgm_name: Some("葬送のフリーレン 第2期".to_string()),
+                relation: RELATION_SEQUEL.to_string(),
+                date: Some("2026-01-16".to_string()),
+            }],
+        };
+        let json = serde_json::to_value(&relation).unwrap();
+        assert_eq!(json["prequel_season"], "2023-fall");
+        assert_eq!(json["chain"][0]["relation"], "续集");
+        assert_eq!(
+            serde_json::from_value::<RelationMatch>(json).unwrap(),
+            relation
+        );
+    }
+}
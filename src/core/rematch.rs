@@ -0,0 +1,138 @@
+use super::eval::season_files;
+use super::{ConfirmStatus, CoreError, SeasonData, SeasonItem, SeasonProcessor};
+use crate::services::llm::MatchDecision;
+use std::path::Path;
+use tracing::info;
+
+/// 重跑统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RematchStats {
+    /// 由旧版本提示判断的条目数
+    pub outdated: usize,
+    /// 重跑后更新的条目数
+    pub updated: usize,
+    /// 重跑失败（查询出错、预算用完等）保留原结果的条目数
+    pub kept: usize,
+}
+
+/// 是否为其他版本提示做出的模型判断（没有记录版本的旧数据也算）
+///
+/// 只处理模型确认（`model`）与模型判断后仍未确认的条目，人工确认的条目不动。
+/// 记录判断之前的 `model` 条目没有 `llm` 字段，同样视为旧判断
+fn is_outdated(item: &SeasonItem, version: &str) -> bool {
+    let outdated = |d: &MatchDecision| d.judge.prompt_version.as_deref() != Some(version);
+    match item.status {
+        ConfirmStatus::Model => item.llm.as_ref().is_none_or(outdated),
+        ConfirmStatus::Unconfirmed => item.llm.as_ref().is_some_and(outdated),
+        _ => false,
+    }
+}
+
+/// 重跑结果是否可以替换原条目（完全匹配或得到了新的模型判断）
+fn is_resolved(item: &SeasonItem) -> bool {
+    item.status == ConfirmStatus::Match
+        || (item.status != ConfirmStatus::Error && item.llm.is_some())
+}
+
+impl SeasonProcessor {
+    /// 重跑发布数据中由旧版本提示判断的条目，结果写回原文件
+    ///
+    /// 未配置 LLM 时不做任何事
+    pub async fn rematch(&self, release_dir: &Path) -> Result<RematchStats, CoreError> {
+        let mut stats = RematchStats::default();
+        let Some(llm) = &self.llm_client else {
+            return Ok(stats);
+        };
+        let version = llm.prompt_template().version();
+
+        for path in season_files(release_dir).await? {
+            let Some(mut data) = SeasonData::load(&path).await? else {
+                continue;
+            };
+            let season = data.season;
+
+            let indices: Vec<usize> = (0..data.items.len())
+                .filter(|&i| is_outdated(&data.items[i], version))
+                .collect();
+            if indices.is_empty() {
+                continue;
+            }
+            info!(season = %season, count = indices.len(), "重跑旧版本提示的判断");
+            stats.outdated += indices.len();
+
+            let mals = indices.iter().map(|&i| data.items[i].mal.clone()).collect();
+            // 只重跑模型判断，续作推断的预选不会替换原条目
+            let items = self.rerun(season, mals, None).await;
+
+            let mut updated = 0;
+            for (index, item) in indices.into_iter().zip(items) {
+                if is_resolved(&item) {
+                    data.items[index] = item;
+                    updated += 1;
+                } else {
+                    stats.kept += 1;
+                }
+            }
+            if updated > 0 {
+                data.update_time = SeasonData::new(season).update_time;
+                data.save(&path).await?;
+                stats.updated += updated;
+                info!(path = %path.display(), updated = updated, "已更新季度数据");
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_util::mal;
+    use crate::services::llm::LlmJudge;
+
+    fn item(status: ConfirmStatus, prompt_version: Option<&str>) -> SeasonItem {
+        SeasonItem {
+            llm: prompt_version.map(|version| MatchDecision {
+                id: None,
+                confidence: None,
+                reason: None,
+                searches: vec![],
+                judge: LlmJudge {
+                    provider: "deepseek".to_string(),
+                    model: "deepseek-chat".to_string(),
+                    prompt_version: (!version.is_empty()).then(|| version.to_string()),
+                },
+            }),
+            ..SeasonItem::new(status, mal(1))
+        }
+    }
+
+    #[test]
+    fn test_is_outdated() {
+        assert!(is_outdated(&item(ConfirmStatus::Model, Some("v1")), "v2"));
+        assert!(is_outdated(
+            &item(ConfirmStatus::Unconfirmed, Some("v1")),
+            "v2"
+        ));
+        // 未记录版本的旧判断
+        assert!(is_outdated(&item(ConfirmStatus::Model, Some("")), "v2"));
+        // 记录判断之前的 model 条目没有 llm 字段
+        assert!(is_outdated(&item(ConfirmStatus::Model, None), "v2"));
+
+        assert!(!is_outdated(&item(ConfirmStatus::Model, Some("v2")), "v2"));
+        // 没有模型判断、人工确认的条目不重跑
+        assert!(!is_outdated(&item(ConfirmStatus::Unconfirmed, None), "v2"));
+        assert!(!is_outdated(&item(ConfirmStatus::Human, Some("v1")), "v2"));
+    }
+
+    #[test]
+    fn test_is_resolved() {
+        assert!(is_resolved(&item(ConfirmStatus::Model, Some("v2"))));
+        assert!(is_resolved(&item(ConfirmStatus::Unconfirmed, Some("v2"))));
+        assert!(is_resolved(&item(ConfirmStatus::Match, None)));
+
+        assert!(!is_resolved(&item(ConfirmStatus::Unconfirmed, None)));
+        assert!(!is_resolved(&item(ConfirmStatus::Error, None)));
+    }
+}
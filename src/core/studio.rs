@@ -0,0 +1,162 @@
+use super::title_match::normalize_title;
+use crate::services::bgmtv::Subject;
+
+/// 制作公司别名表：同一行为同一家公司（MAL 多为罗马字，Bangumi 信息框多为日文）
+///
+/// 规范化（大小写、空白、标点）后相同的写法不必列出，如 `J.C.Staff` 与 `J.C.STAFF`
+const STUDIO_ALIASES: &[&[&str]] = &[
+    &["J.C.Staff", "ジェー・シー・スタッフ"],
+    &["Madhouse", "マッドハウス"],
+    &[
+        "Production I.G",
+        "プロダクション・アイジー",
+        "Production IG",
+    ],
+    &["Kyoto Animation", "京都アニメーション", "京アニ"],
+    &["Toei Animation", "東映アニメーション", "东映动画"],
+    &["Sunrise", "サンライズ"],
+    &["Bandai Namco Pictures", "バンダイナムコピクチャーズ"],
+    &["Bones", "ボンズ"],
+    &["Shaft", "シャフト"],
+    &["Pierrot", "Studio Pierrot", "ぴえろ", "スタジオぴえろ"],
+    &["Studio Deen", "スタジオディーン"],
+    &["TMS Entertainment", "トムス・エンタテインメント"],
+    &["Doga Kobo", "動画工房"],
+    &["P.A. Works", "ピーエーワークス"],
+    &["Shin-Ei Animation", "シンエイ動画"],
+    &["OLM", "オー・エル・エム"],
+    &["Lerche", "ラルケ"],
+    &["Brain's Base", "ブレインズ・ベース"],
+    &["Studio Ghibli", "スタジオジブリ"],
+    &["Tatsunoko Production", "タツノコプロ"],
+    &["Nippon Animation", "日本アニメーション"],
+    &["Gainax", "ガイナックス"],
+    &["Trigger", "トリガー"],
+    &["Science SARU", "サイエンスSARU"],
+    &["David Production", "デイヴィッドプロダクション"],
+    &["Kinema Citrus", "キネマシトラス"],
+    &["Gonzo", "ゴンゾ"],
+    &["feel.", "フィール"],
+    &["Silver Link.", "シルバーリンク"],
+    &["White Fox", "ホワイトフォックス"],
+    &["Liden Films", "ライデンフィルム"],
+    &["Passione", "パッショーネ"],
+    &["Ajia-do", "亜細亜堂"],
+    &["Tezuka Productions", "手塚プロダクション"],
+    &["Telecom Animation Film", "テレコム・アニメーションフィルム"],
+    &["Actas", "アクタス"],
+    &["8bit", "エイトビット"],
+    &["Studio Bind", "スタジオバインド"],
+    &["Studio Kai", "スタジオKAI"],
+    &["Studio Gokumi", "スタジオ五組"],
+    &["Satelight", "サテライト"],
+    &["Diomedéa", "ディオメディア"],
+    &["Zero-G", "ゼロジー"],
+    &["SynergySP", "シナジーSP"],
+    &["Polygon Pictures", "ポリゴン・ピクチュアズ"],
+    &["Orange", "オレンジ"],
+    &["Studio Signpost", "スタジオサインポスト"],
+    &["Yokohama Animation Lab", "横浜アニメーションラボ"],
+];
+
+/// 比较用的制作公司名：规范化后，别名表中的写法统一为第一个
+fn studio_key(name: &str) -> String {
+    let normalized = normalize_title(name);
+    STUDIO_ALIASES
+        .iter()
+        .find(|aliases| aliases.iter().any(|a| normalize_title(a) == normalized))
+        .map(|aliases| normalize_title(aliases[0]))
+        .unwrap_or(normalized)
+}
+
+/// MAL 制作公司与 Bangumi 信息框 `动画制作` 是否有同一家（联合制作时任一家一致即可）
+///
+/// 任一方没有制作公司时无法判断，返回 None
+pub(super) fn studios_agree<S: AsRef<str>>(mal: &[&str], bgm: &[S]) -> Option<bool> {
+    let mal: Vec<String> = mal
+        .iter()
+        .map(|s| studio_key(s))
+        .filter(|s| !s.is_empty())
+        .collect();
+    let bgm: Vec<String> = bgm
+        .iter()
+        .map(|s| studio_key(s.as_ref()))
+        .filter(|s| !s.is_empty())
+        .collect();
+    if mal.is_empty() || bgm.is_empty() {
+        return None;
+    }
+    Some(bgm.iter().any(|s| mal.contains(s)))
+}
+
+/// 按制作公司排序搜索结果：一致的在前，无法判断的居中，不一致的在后（同组内保持搜索顺序）
+pub(super) fn rank_by_studio(mal_studios: &[&str], subjects: &mut [Subject]) {
+    if mal_studios.is_empty() {
+        return;
+    }
+    subjects.sort_by_cached_key(|subject| {
+        match studios_agree(mal_studios, &subject.infobox_fields().studios) {
+            Some(true) => 0,
+            None => 1,
+            Some(false) => 2,
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subject(id: u64, studio: Option<&str>) -> Subject {
+        let infobox: Vec<_> = studio
+            .map(|s| serde_json::json!({ "key": "动画制作", "value": s }))
+            .into_iter()
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "type": 2,
+            "infobox": infobox
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_studios_agree() {
+        assert_eq!(studios_agree(&["J.C.Staff"], &["J.C.STAFF"]), Some(true));
+        assert_eq!(studios_agree(&["Madhouse"], &["マッドハウス"]), Some(true));
+        assert_eq!(
+            studios_agree(&["Production I.G"], &["プロダクション・アイジー"]),
+            Some(true)
+        );
+        // 联合制作
+        assert_eq!(
+            studios_agree(&["Wit Studio", "CloverWorks"], &["CloverWorks"]),
+            Some(true)
+        );
+        assert_eq!(
+            studios_agree(&["Madhouse"], &["東映アニメーション"]),
+            Some(false)
+        );
+        assert_eq!(studios_agree::<&str>(&["Madhouse"], &[]), None);
+        assert_eq!(studios_agree(&[], &["マッドハウス"]), None);
+    }
+
+    #[test]
+    fn test_rank_by_studio() {
+        // 1990 年代原作与 2020 年代重制，标题相近
+        let mut subjects = vec![
+            subject(1, Some("東映アニメーション")),
+            subject(2, None),
+            subject(3, Some("マッドハウス")),
+            subject(4, Some("MADHOUSE")),
+        ];
+        rank_by_studio(&["Madhouse"], &mut subjects);
+        let ids: Vec<_> = subjects.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![3, 4, 2, 1]);
+
+        // MAL 没有制作公司时不改变顺序
+        rank_by_studio(&[], &mut subjects);
+        let ids: Vec<_> = subjects.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![3, 4, 2, 1]);
+    }
+}
@@ -0,0 +1,23 @@
+use super::{ConfirmStatus, MalInfo, MediaType, Rating, SeasonItem};
+
+/// MAL 信息（TV，一般向，标题为 `anime {id}`）
+pub(super) fn mal(id: u64) -> MalInfo {
+    MalInfo {
+        id,
+        title: format!("anime {}", id),
+        title_ja: None,
+        title_en: None,
+        synonyms: vec![],
+        media_type: MediaType::Tv,
+        rating: Rating::General,
+    }
+}
+
+/// 映射到 Bangumi 条目 `id * 10`（名称 `subject {id * 10}`）的条目
+pub(super) fn with_bgm(status: ConfirmStatus, id: u64) -> SeasonItem {
+    SeasonItem {
+        bgm_id: Some(id * 10),
+        bgm_name: Some(format!("subject {}", id * 10)),
+        ..SeasonItem::new(status, mal(id))
+    }
+}
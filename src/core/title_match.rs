@@ -0,0 +1,261 @@
+use crate::services::bgmtv::Subject;
+use crate::services::mal::AnimeNode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 别名匹配：规范化后一致的 MAL 标题与 Bangumi 名称
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AliasMatch {
+    /// MAL 标题（`title`、日文标题、英文标题或同义名）
+    pub mal_title: String,
+    /// Bangumi 名称（`name`、`name_cn` 或信息框别名）
+    pub bgm_title: String,
+}
+
+/// 比较前去掉的标点与符号（全角 ASCII 已先转为半角）
+const IGNORED_CHARS: &[char] = &[
+    '・', '･', '、', '。', '「', '」', '『', '』', '【', '】', '〈', '〉', '《', '》', '〜', '～',
+    '…', '‐', '–', '—', '―', '‘', '’', '“', '”', '☆', '★', '♪', '♡', '♥', '†',
+];
+
+/// 规范化标题：全角 ASCII 转半角、小写，去掉空白与标点
+pub(super) fn normalize_title(title: &str) -> String {
+    title
+        .chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .filter(|c| !c.is_whitespace() && !c.is_ascii_punctuation() && !IGNORED_CHARS.contains(c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// MAL 的所有标题：`title`、日文标题、英文标题、同义名
+pub(super) fn mal_titles(anime: &AnimeNode) -> Vec<&str> {
+    let alternative = anime.alternative_titles.as_ref();
+    std::iter::once(anime.title.as_str())
+        .chain(alternative.and_then(|t| t.ja.as_deref()))
+        .chain(alternative.and_then(|t| t.en.as_deref()))
+        .chain(
+            alternative
+                .into_iter()
+                .flat_map(|t| t.synonyms.iter().map(String::as_str)),
+        )
+        .collect()
+}
+
+/// Bangumi 条目的所有名称：`name`、`name_cn`、信息框别名
+fn bgm_titles(subject: &Subject) -> Vec<String> {
+    subject
+        .name
+        .iter()
+        .chain(subject.name_cn.iter())
+        .cloned()
+        .chain(subject.infobox_fields().aliases)
+        .collect()
+}
+
+/// 条目的任一名称与 MAL 的任一标题规范化后一致时返回匹配的一对
+fn alias_pair(titles: &[(&str, String)], subject: &Subject) -> Option<AliasMatch> {
+    bgm_titles(subject).into_iter().find_map(|bgm_title| {
+        let normalized = normalize_title(&bgm_title);
+        if normalized.is_empty() {
+            return None;
+        }
+        let (mal_title, _) = titles.iter().find(|(_, t)| *t == normalized)?;
+        Some(AliasMatch {
+            mal_title: mal_title.to_string(),
+            bgm_title,
+        })
+    })
+}
+
+/// 字符二元组（只有一个字符时为该字符本身）
+fn bigrams(title: &str) -> Vec<(char, char)> {
+    let chars: Vec<char> = title.chars().collect();
+    match chars.as_slice() {
+        [c] => vec![(*c, *c)],
+        _ => chars.windows(2).map(|w| (w[0], w[1])).collect(),
+    }
+}
+
+/// 两个规范化标题的 Dice 系数
+fn dice(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let (a, b) = (bigrams(a), bigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let mut counts: HashMap<(char, char), usize> = HashMap::new();
+    for pair in &a {
+        *counts.entry(*pair).or_default() += 1;
+    }
+    let mut common = 0;
+    for pair in &b {
+        if let Some(n) = counts.get_mut(pair)
+            && *n > 0
+        {
+            *n -= 1;
+            common += 1;
+        }
+    }
+    2.0 * common as f64 / (a.len() + b.len()) as f64
+}
+
+/// 标题相似度（0 ~ 1，保留两位小数）：MAL 各标题与 Bangumi 各名称规范化后字符二元组的 Dice 系数，取最大值
+pub(super) fn names_similarity<S: AsRef<str>>(mal_titles: &[&str], bgm_names: &[S]) -> f64 {
+    let bgm: Vec<String> = bgm_names
+        .iter()
+        .map(|t| normalize_title(t.as_ref()))
+        .filter(|t| !t.is_empty())
+        .collect();
+    let best = mal_titles
+        .iter()
+        .map(|t| normalize_title(t))
+        .filter(|t| !t.is_empty())
+        .flat_map(|mal| bgm.iter().map(move |b| dice(&mal, b)))
+        .fold(0.0, f64::max);
+    (best * 100.0).round() / 100.0
+}
+
+/// MAL 标题与条目名称、中文名、信息框别名的相似度
+pub(super) fn title_similarity(mal_titles: &[&str], subject: &Subject) -> f64 {
+    names_similarity(mal_titles, &bgm_titles(subject))
+}
+
+/// 在搜索结果中查找别名匹配，返回条目下标与匹配的一对
+///
+/// 多个条目都匹配（如总集篇与正片同名）时无法判断，返回 None 留给模型
+pub(super) fn find_alias_match(
+    anime: &AnimeNode,
+    subjects: &[Subject],
+) -> Option<(usize, AliasMatch)> {
+    let titles: Vec<(&str, String)> = mal_titles(anime)
+        .into_iter()
+        .map(|t| (t, normalize_title(t)))
+        .filter(|(_, normalized)| !normalized.is_empty())
+        .collect();
+
+    let mut matches = subjects
+        .iter()
+        .enumerate()
+        .filter_map(|(index, subject)| Some((index, alias_pair(&titles, subject)?)));
+    let first = matches.next()?;
+    matches.next().is_none().then_some(first)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anime(title: &str, ja: Option<&str>, en: Option<&str>, synonyms: &[&str]) -> AnimeNode {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "title": title,
+            "alternative_titles": {
+                "ja": ja,
+                "en": en,
+                "synonyms": synonyms,
+            }
+        }))
+        .unwrap()
+    }
+
+    fn subject(id: u64, name: &str, name_cn: &str, aliases: &[&str]) -> Subject {
+        let aliases: Vec<_> = aliases
+            .iter()
+            .map(|a| serde_json::json!({ "v": a }))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "type": 2,
+            "name": name,
+            "name_cn": name_cn,
+            "infobox": [{ "key": "别名", "value": aliases }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_normalize_title() {
+        assert_eq!(normalize_title("ＳＰＹ×ＦＡＭＩＬＹ"), "spy×family");
+        assert_eq!(
+            normalize_title("Frieren: Beyond Journey's End"),
+            "frierenbeyondjourneysend"
+        );
+        assert_eq!(
+            normalize_title("ぼっち・ざ・ろっく！"),
+            normalize_title("ぼっちざろっく!")
+        );
+        assert_eq!(normalize_title("☆・"), "");
+    }
+
+    #[test]
+    fn test_title_similarity() {
+        let frieren = subject(400602, "葬送のフリーレン", "葬送的芙莉莲", &[]);
+        assert_eq!(title_similarity(&["葬送のフリーレン"], &frieren), 1.0);
+        assert_eq!(title_similarity(&["ソウソウノフリーレン"], &frieren), 0.5);
+        let sequel = title_similarity(&["葬送のフリーレン 第2期"], &frieren);
+        assert!(sequel > 0.7 && sequel < 1.0);
+        assert_eq!(title_similarity(&["ガンダム"], &frieren), 0.0);
+        assert_eq!(title_similarity(&[], &frieren), 0.0);
+    }
+
+    #[test]
+    fn test_find_alias_match() {
+        let frieren = anime(
+            "Sousou no Frieren 2nd Season",
+            Some("葬送のフリーレン 第2期"),
+            Some("Frieren: Beyond Journey's End Season 2"),
+            &["Frieren at the Funeral Season 2"],
+        );
+        let subjects = vec![
+            subject(400602, "葬送のフリーレン", "葬送的芙莉莲", &["Frieren"]),
+            subject(
+                515759,
+                "葬送のフリーレン 第二期",
+                "葬送的芙莉莲 第二季",
+                &[
+                    "Frieren: Beyond Journey’s End Season 2",
+                    "Sousou no Frieren 2nd Season",
+                ],
+            ),
+        ];
+        let (index, alias) = find_alias_match(&frieren, &subjects).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(
+            alias,
+            AliasMatch {
+                mal_title: "Frieren: Beyond Journey's End Season 2".to_string(),
+                bgm_title: "Frieren: Beyond Journey’s End Season 2".to_string(),
+            }
+        );
+
+        // 英文标题与 name 一致
+        let spy = anime("Spy x Family", None, Some("SPY×FAMILY"), &[]);
+        let subjects = vec![subject(329906, "SPY×FAMILY", "间谍过家家", &[])];
+        let (_, alias) = find_alias_match(&spy, &subjects).unwrap();
+        assert_eq!(alias.mal_title, "SPY×FAMILY");
+
+        // 多个条目匹配时不判断
+        let subjects = vec![
+            subject(1, "Bocchi the Rock!", "", &[]),
+            subject(2, "ぼっち・ざ・ろっく！", "", &["Bocchi the Rock!"]),
+        ];
+        let bocchi = anime("Bocchi the Rock!", None, None, &[]);
+        assert_eq!(find_alias_match(&bocchi, &subjects), None);
+
+        // 没有别名也没有一致的标题
+        let kusuriya = anime("Kusuriya no Hitorigoto", None, None, &[]);
+        assert_eq!(
+            find_alias_match(
+                &kusuriya,
+                &[subject(3, "薬屋のひとりごと", "药屋少女的呢喃", &[])]
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,342 @@
+use super::eval::season_files;
+use super::{
+    BgmCandidate, ConfirmStatus, CoreError, SeasonData, SeasonProcessor, YearSeason, save_json,
+};
+use crate::services::bgmtv::{BgmtvError, Subject, SubjectType};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 核对时同时进行的条目详情请求数
+pub const DEFAULT_VERIFY_CONCURRENCY: usize = 4;
+
+/// 核对时缓存的条目详情的有效期：中断后重新运行时跳过已获取的条目，超过后重新获取
+pub const DEFAULT_VERIFY_CACHE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 映射的 Bangumi 条目的问题
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VerifyIssue {
+    /// 条目不存在（已删除，HTTP 404）
+    NotFound,
+    /// 已合并到其他条目（请求被重定向）
+    Merged { into: u64 },
+    /// 条目类型不再是动画
+    NotAnime { subject_type: u8 },
+    /// 条目已锁定
+    Locked,
+}
+
+/// 有问题的已确认映射
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerifyFinding {
+    pub season: YearSeason,
+    pub mal_id: u64,
+    pub mal_title: String,
+    pub bgm_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bgm_name: Option<String>,
+    pub status: ConfirmStatus,
+    pub issue: VerifyIssue,
+}
+
+/// 已确认映射的核对报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyReport {
+    pub update_time: String,
+    /// 核对的 Bangumi 条目数（去重后）
+    pub checked: usize,
+    /// 请求失败（404 以外的错误）无法核对的条目数
+    pub failed: usize,
+    pub findings: Vec<VerifyFinding>,
+    /// 重置为未确认的条目数
+    pub reset: usize,
+}
+
+impl VerifyReport {
+    /// 保存到文件
+    pub async fn save(&self, path: &Path) -> Result<(), CoreError> {
+        save_json(self, path).await
+    }
+}
+
+/// 核对获取到的条目：ID 不同说明已合并，其次检查类型与锁定
+fn subject_issue(requested: u64, subject: &Subject) -> Option<VerifyIssue> {
+    if subject.id != requested {
+        Some(VerifyIssue::Merged { into: subject.id })
+    } else if subject.subject_type != u8::from(SubjectType::Anime) {
+        Some(VerifyIssue::NotAnime {
+            subject_type: subject.subject_type,
+        })
+    } else if subject.locked == Some(true) {
+        Some(VerifyIssue::Locked)
+    } else {
+        None
+    }
+}
+
+/// 列出季度数据中映射有问题的已确认条目，`reset` 时将其重置为未确认
+///
+/// 已合并的条目以合并后的条目作为预选与候选，其他问题清除 `bgm_id`
+fn apply_issues(
+    data: &mut SeasonData,
+    issues: &HashMap<u64, (VerifyIssue, Option<Subject>)>,
+    reset: bool,
+) -> Vec<VerifyFinding> {
+    let mut findings = Vec::new();
+    for item in data.items.iter_mut().filter(|i| i.status.is_confirmed()) {
+        let Some(bgm_id) = item.bgm_id else {
+            continue;
+        };
+        let Some((issue, subject)) = issues.get(&bgm_id) else {
+            continue;
+        };
+        findings.push(VerifyFinding {
+            season: data.season,
+            mal_id: item.mal.id,
+            mal_title: item.mal.title.clone(),
+            bgm_id,
+            bgm_name: item.bgm_name.clone(),
+            status: item.status,
+            issue: *issue,
+        });
+        if !reset {
+            continue;
+        }
+
+        // 之前的判断依据都指向原条目，一并清除
+        item.status = ConfirmStatus::Unconfirmed;
+        item.llm = None;
+        item.llm_second = None;
+        item.relation = None;
+        item.alias_match = None;
+        match (issue, subject) {
+            (VerifyIssue::Merged { .. }, Some(subject)) => {
+                let candidate = BgmCandidate::from(subject);
+                item.bgm_id = Some(candidate.bgm_id);
+                item.bgm_name = Some(candidate.bgm_name.clone());
+                item.bgm_name_cn = candidate.bgm_name_cn.clone();
+                if !item.candidates.iter().any(|c| c.bgm_id == candidate.bgm_id) {
+                    item.candidates.push(candidate);
+                }
+            }
+            _ => {
+                item.bgm_id = None;
+                item.bgm_name = None;
+                item.bgm_name_cn = None;
+            }
+        }
+    }
+    findings
+}
+
+impl SeasonProcessor {
+    /// 重新获取发布数据中所有已确认映射的 Bangumi 条目，报告已删除、合并、类型变更或锁定的条目
+    ///
+    /// `reset` 时将这些条目重置为未确认并写回原文件，下次更新时重新匹配
+    pub async fn verify(
+        &self,
+        release_dir: &Path,
+        concurrency: usize,
+        reset: bool,
+    ) -> Result<VerifyReport, CoreError> {
+        let mut seasons = Vec::new();
+        for path in season_files(release_dir).await? {
+            if let Some(data) = SeasonData::load(&path).await? {
+                seasons.push((path, data));
+            }
+        }
+
+        // 多个条目可能映射到同一个 Bangumi 条目，只请求一次
+        let ids: Vec<u64> = seasons
+            .iter()
+            .flat_map(|(_, data)| &data.items)
+            .filter(|item| item.status.is_confirmed())
+            .filter_map(|item| item.bgm_id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        info!(
+            count = ids.len(),
+            concurrency = concurrency,
+            "核对已确认映射"
+        );
+
+        let results = self.bgm_client.get_subjects(&ids, concurrency).await;
+        let mut issues = HashMap::new();
+        let mut failed = 0;
+        for (bgm_id, result) in ids.iter().copied().zip(results) {
+            match result {
+                Ok(subject) => {
+                    if let Some(issue) = subject_issue(bgm_id, &subject) {
+                        issues.insert(bgm_id, (issue, Some(subject)));
+                    }
+                }
+                Err(BgmtvError::NotFound(_)) => {
+                    issues.insert(bgm_id, (VerifyIssue::NotFound, None));
+                }
+                Err(e) => {
+                    warn!(bgm_id = bgm_id, error = %e, "获取 Bangumi 条目详情失败");
+                    failed += 1;
+                }
+            }
+        }
+
+        let mut findings = Vec::new();
+        let mut reset_count = 0;
+        for (path, mut data) in seasons {
+            let season_findings = apply_issues(&mut data, &issues, reset);
+            if season_findings.is_empty() {
+                continue;
+            }
+            if reset {
+                data.update_time = SeasonData::new(data.season).update_time;
+                data.save(&path).await?;
+                reset_count += season_findings.len();
+                info!(
+                    path = %path.display(),
+                    reset = season_findings.len(),
+                    "已重置为未确认"
+                );
+            }
+            findings.extend(season_findings);
+        }
+
+        Ok(VerifyReport {
+            update_time: Local::now()
+                .fixed_offset()
+                .format("%Y-%m-%dT%H:%M:%S%:z")
+                .to_string(),
+            checked: ids.len(),
+            failed,
+            findings,
+            reset: reset_count,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_util::with_bgm;
+    use crate::core::{AliasMatch, RelationMatch, SeasonItem};
+    use crate::services::llm::MatchDecision;
+    use crate::services::mal::Season;
+
+    fn subject(id: u64, subject_type: u8, locked: bool) -> Subject {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "type": subject_type,
+            "name": format!("subject {}", id),
+            "locked": locked
+        }))
+        .unwrap()
+    }
+
+    /// 带模型判断、续作推断与别名匹配记录的已映射条目
+    fn with_history(status: ConfirmStatus, id: u64) -> SeasonItem {
+        let decision: MatchDecision = serde_json::from_value(serde_json::json!({
+            "bgm_id": id * 10,
+            "confidence": "high",
+            "provider": "test",
+            "model": "test"
+        }))
+        .unwrap();
+        SeasonItem {
+            llm: Some(decision.clone()),
+            llm_second: Some(decision),
+            relation: Some(RelationMatch {
+                prequel_mal_id: 100,
+                prequel_season: YearSeason::new(2025, Season::Fall),
+                prequel_bgm_id: 1000,
+                chain: vec![],
+            }),
+            alias_match: Some(AliasMatch {
+                mal_title: format!("anime {}", id),
+                bgm_title: format!("subject {}", id * 10),
+            }),
+            ..with_bgm(status, id)
+        }
+    }
+
+    /// 是否还有之前的判断依据
+    fn has_history(item: &SeasonItem) -> bool {
+        item.llm.is_some()
+            || item.llm_second.is_some()
+            || item.relation.is_some()
+            || item.alias_match.is_some()
+    }
+
+    #[test]
+    fn test_subject_issue() {
+        assert_eq!(subject_issue(1, &subject(1, 2, false)), None);
+        assert_eq!(
+            subject_issue(1, &subject(2, 2, false)),
+            Some(VerifyIssue::Merged { into: 2 })
+        );
+        assert_eq!(
+            subject_issue(1, &subject(1, 6, false)),
+            Some(VerifyIssue::NotAnime { subject_type: 6 })
+        );
+        assert_eq!(
+            subject_issue(1, &subject(1, 2, true)),
+            Some(VerifyIssue::Locked)
+        );
+    }
+
+    #[test]
+    fn test_apply_issues() {
+        let mut data = SeasonData::new(YearSeason::new(2026, Season::Winter));
+        data.items = vec![
+            with_history(ConfirmStatus::Match, 1),
+            with_history(ConfirmStatus::Human, 2),
+            with_history(ConfirmStatus::Model, 3),
+            // 未确认条目的预选不核对
+            with_bgm(ConfirmStatus::Unconfirmed, 4),
+        ];
+        let issues = HashMap::from([
+            (10, (VerifyIssue::NotFound, None)),
+            (
+                20,
+                (
+                    VerifyIssue::Merged { into: 25 },
+                    Some(subject(25, 2, false)),
+                ),
+            ),
+            (40, (VerifyIssue::Locked, Some(subject(40, 2, true)))),
+        ]);
+
+        // 只报告，不修改
+        let findings = apply_issues(&mut data, &issues, false);
+        let ids: Vec<_> = findings.iter().map(|f| (f.mal_id, f.issue)).collect();
+        assert_eq!(
+            ids,
+            vec![
+                (1, VerifyIssue::NotFound),
+                (2, VerifyIssue::Merged { into: 25 })
+            ]
+        );
+        assert_eq!(data.items[0].status, ConfirmStatus::Match);
+        assert!(has_history(&data.items[0]));
+
+        let findings = apply_issues(&mut data, &issues, true);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(data.items[0].status, ConfirmStatus::Unconfirmed);
+        assert_eq!(data.items[0].bgm_id, None);
+        // 合并后的条目作为预选与候选
+        assert_eq!(data.items[1].status, ConfirmStatus::Unconfirmed);
+        assert_eq!(data.items[1].bgm_id, Some(25));
+        assert_eq!(data.items[1].candidates[0].bgm_id, 25);
+        // 之前的模型判断、续作推断与别名匹配不再适用
+        assert!(!has_history(&data.items[0]));
+        assert!(!has_history(&data.items[1]));
+        assert_eq!(data.items[2].status, ConfirmStatus::Model);
+        assert!(has_history(&data.items[2]));
+
+        // 重置后不再报告
+        assert!(apply_issues(&mut data, &issues, true).is_empty());
+    }
+}
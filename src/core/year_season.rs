@@ -0,0 +1,369 @@
+use crate::services::mal::Season;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, TimeZone};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const SEASONS: [Season; 4] = [Season::Winter, Season::Spring, Season::Summer, Season::Fall];
+
+/// 季度按日本放送季划分，默认使用日本标准时间判断当前日期
+pub const DEFAULT_SEASON_TIMEZONE: &str = "+09:00";
+
+/// 解析时区偏移：`+09:00`、`-0500`、`UTC`/`Z`
+pub fn parse_utc_offset(s: &str) -> Result<FixedOffset, String> {
+    let s = s.trim();
+    if s.eq_ignore_ascii_case("utc") || s == "Z" {
+        return Ok(FixedOffset::east_opt(0).expect("UTC"));
+    }
+    let invalid = || format!("无效的时区偏移: {}（如 +09:00）", s);
+    let sign = match s.chars().next() {
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return Err(invalid()),
+    };
+    let digits = s[1..].replace(':', "");
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let hours: i32 = digits[..2].parse().map_err(|_| invalid())?;
+    let minutes: i32 = digits[2..].parse().map_err(|_| invalid())?;
+    if minutes >= 60 {
+        return Err(invalid());
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
+
+/// 年份与季度（如 `2026-winter`），按时间先后排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct YearSeason {
+    pub year: u32,
+    pub season: Season,
+}
+
+impl YearSeason {
+    pub fn new(year: u32, season: Season) -> Self {
+        Self { year, season }
+    }
+
+    /// 年月所在的季度（1-3 月为冬季，以此类推）
+    pub fn from_year_month(year: u32, month: u32) -> Option<Self> {
+        Some(Self::new(year, Season::from_month(month)?))
+    }
+
+    /// 日期所在的季度（公元前的日期为 None）
+    pub fn from_date(date: NaiveDate) -> Option<Self> {
+        Self::from_year_month(u32::try_from(date.year()).ok()?, date.month())
+    }
+
+    /// 某一时刻在指定时区所在的季度
+    pub fn at<Tz: TimeZone>(time: DateTime<Tz>, offset: FixedOffset) -> Option<Self> {
+        Self::from_date(time.with_timezone(&offset).date_naive())
+    }
+
+    /// 最近的季度：上一季度（MAL 仍会补录条目）、当前季度与下一季度
+    pub fn recent(self) -> SeasonRange {
+        SeasonRange {
+            next: self.prev().or(Some(self)),
+            end: self.next().unwrap_or(self),
+        }
+    }
+
+    /// `all-seasons.json` 中的季度代码：冬 1、春 4、夏 7、秋 10
+    pub fn month_code(&self) -> u32 {
+        match self.season {
+            Season::Winter => 1,
+            Season::Spring => 4,
+            Season::Summer => 7,
+            Season::Fall => 10,
+        }
+    }
+
+    /// 由季度代码还原（只接受 1/4/7/10）
+    pub fn from_month_code(year: u32, code: u32) -> Option<Self> {
+        match code {
+            1 | 4 | 7 | 10 => Self::from_year_month(year, code),
+            _ => None,
+        }
+    }
+
+    fn index(&self) -> usize {
+        SEASONS.iter().position(|s| *s == self.season).unwrap_or(0)
+    }
+
+    /// 下一个季度（年份溢出时为 None）
+    pub fn next(&self) -> Option<Self> {
+        match self.index() {
+            3 => Some(Self::new(self.year.checked_add(1)?, Season::Winter)),
+            i => Some(Self::new(self.year, SEASONS[i + 1])),
+        }
+    }
+
+    /// 上一个季度（0 年冬季之前为 None）
+    pub fn prev(&self) -> Option<Self> {
+        match self.index() {
+            0 => Some(Self::new(self.year.checked_sub(1)?, Season::Fall)),
+            i => Some(Self::new(self.year, SEASONS[i - 1])),
+        }
+    }
+
+    /// 从当前季度到 `end` 的闭区间（`end` 早于当前季度时为空）
+    pub fn through(self, end: Self) -> SeasonRange {
+        SeasonRange {
+            next: Some(self),
+            end,
+        }
+    }
+
+    /// 用于搜索 Bangumi 的季度日期范围（闭区间，向前多包含一个月）
+    ///
+    /// 冬季 12/1 ~ 3/31（跨年），春季 3/1 ~ 6/30，夏季 6/1 ~ 9/30，秋季 9/1 ~ 12/31
+    pub fn air_window(&self) -> (NaiveDate, NaiveDate) {
+        let year = i32::try_from(self.year).unwrap_or(i32::MAX);
+        let date = |year: i32, month, day| {
+            NaiveDate::from_ymd_opt(year, month, day).unwrap_or(NaiveDate::MAX)
+        };
+        match self.season {
+            Season::Winter => (date(year - 1, 12, 1), date(year, 3, 31)),
+            Season::Spring => (date(year, 3, 1), date(year, 6, 30)),
+            Season::Summer => (date(year, 6, 1), date(year, 9, 30)),
+            Season::Fall => (date(year, 9, 1), date(year, 12, 31)),
+        }
+    }
+
+    /// 季度数据文件 `{release_dir}/{year}/{year}-{season}-mal.json`
+    pub fn release_file(&self, release_dir: &Path) -> PathBuf {
+        release_dir
+            .join(self.year.to_string())
+            .join(format!("{}-mal.json", self))
+    }
+
+    /// 补充了 Bangumi 条目详情的输出 `{release_dir}/{year}/{year}-{season}-enriched.json`
+    pub fn enriched_file(&self, release_dir: &Path) -> PathBuf {
+        release_dir
+            .join(self.year.to_string())
+            .join(format!("{}-enriched.json", self))
+    }
+
+    /// 与 Bangumi 每日放送的对照报告 `{release_dir}/{year}/{year}-{season}-calendar.json`
+    pub fn calendar_file(&self, release_dir: &Path) -> PathBuf {
+        release_dir
+            .join(self.year.to_string())
+            .join(format!("{}-calendar.json", self))
+    }
+
+    /// 由季度数据文件路径解析季度（文件名不符合时为 None）
+    pub fn from_release_file(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        name.strip_suffix("-mal.json")?.parse().ok()
+    }
+}
+
+impl fmt::Display for YearSeason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.year, self.season)
+    }
+}
+
+impl FromStr for YearSeason {
+    type Err = String;
+
+    /// `2026-winter`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("无效的季度: {}（如 2026-winter）", s);
+        let (year, season) = s.trim().split_once('-').ok_or_else(invalid)?;
+        Ok(Self::new(
+            year.parse().map_err(|_| invalid())?,
+            season.parse().map_err(|_| invalid())?,
+        ))
+    }
+}
+
+impl Serialize for YearSeason {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for YearSeason {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// 季度闭区间，按时间顺序迭代
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeasonRange {
+    next: Option<YearSeason>,
+    end: YearSeason,
+}
+
+impl Iterator for SeasonRange {
+    type Item = YearSeason;
+
+    fn next(&mut self) -> Option<YearSeason> {
+        let current = self.next.filter(|current| *current <= self.end)?;
+        self.next = current.next();
+        Some(current)
+    }
+}
+
+impl FromStr for SeasonRange {
+    type Err = String;
+
+    /// `2024-spring`（单个季度）或 `2020-winter..2025-fall`（包含两端）
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.split_once("..").unwrap_or((s, s));
+        let (start, end): (YearSeason, YearSeason) = (start.parse()?, end.parse()?);
+        if end < start {
+            return Err(format!("季度范围的结束早于开始: {}", s));
+        }
+        Ok(start.through(end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ys(s: &str) -> YearSeason {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!(ys("2026-winter"), YearSeason::new(2026, Season::Winter));
+        assert_eq!(ys("2024-fall").to_string(), "2024-fall");
+
+        assert!("2024".parse::<YearSeason>().is_err());
+        assert!("2024-autumn".parse::<YearSeason>().is_err());
+        assert!("-winter".parse::<YearSeason>().is_err());
+
+        let json = serde_json::to_string(&ys("2026-winter")).unwrap();
+        assert_eq!(json, "\"2026-winter\"");
+        assert_eq!(
+            serde_json::from_str::<YearSeason>(&json).unwrap(),
+            ys("2026-winter")
+        );
+        assert!(serde_json::from_str::<YearSeason>("\"2026\"").is_err());
+    }
+
+    #[test]
+    fn test_next_prev() {
+        assert!(ys("2025-fall") < ys("2026-winter"));
+        assert_eq!(ys("2025-fall").next(), Some(ys("2026-winter")));
+        assert_eq!(ys("2026-winter").prev(), Some(ys("2025-fall")));
+        assert_eq!(ys("2026-summer").prev(), Some(ys("2026-spring")));
+
+        assert_eq!(ys("0-winter").prev(), None);
+        assert_eq!(YearSeason::new(u32::MAX, Season::Fall).next(), None);
+    }
+
+    #[test]
+    fn test_range() {
+        let seasons: Vec<_> = ys("2025-summer").through(ys("2026-spring")).collect();
+        assert_eq!(
+            seasons,
+            vec![
+                ys("2025-summer"),
+                ys("2025-fall"),
+                ys("2026-winter"),
+                ys("2026-spring")
+            ]
+        );
+        assert_eq!(ys("2026-spring").through(ys("2025-fall")).count(), 0);
+
+        let range: SeasonRange = "2020-winter..2025-fall".parse().unwrap();
+        assert_eq!(range.count(), 24);
+        let range: SeasonRange = "2024-spring".parse().unwrap();
+        assert_eq!(range.collect::<Vec<_>>(), vec![ys("2024-spring")]);
+        assert!("2025-fall..2020-winter".parse::<SeasonRange>().is_err());
+    }
+
+    #[test]
+    fn test_dates_and_codes() {
+        let date = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        assert_eq!(
+            YearSeason::from_date(date("2026-03-31")),
+            Some(ys("2026-winter"))
+        );
+        assert_eq!(YearSeason::from_year_month(2024, 10), Some(ys("2024-fall")));
+        assert_eq!(YearSeason::from_year_month(2024, 13), None);
+
+        assert_eq!(ys("2024-summer").month_code(), 7);
+        assert_eq!(YearSeason::from_month_code(2024, 10), Some(ys("2024-fall")));
+        assert_eq!(YearSeason::from_month_code(2024, 2), None);
+
+        assert_eq!(
+            ys("2026-winter").air_window(),
+            (date("2025-12-01"), date("2026-03-31"))
+        );
+        assert_eq!(
+            ys("2026-spring").air_window(),
+            (date("2026-03-01"), date("2026-06-30"))
+        );
+        // 0 年冬季从公元前 1 年 12 月开始
+        assert_eq!(ys("0-winter").air_window().0, date("-0001-12-01"));
+    }
+
+    #[test]
+    fn test_recent() {
+        let jst = parse_utc_offset(DEFAULT_SEASON_TIMEZONE).unwrap();
+        // UTC 3 月 31 日 16:00 在日本已是 4 月 1 日
+        let time = DateTime::parse_from_rfc3339("2026-03-31T16:00:00Z").unwrap();
+        assert_eq!(YearSeason::at(time, jst), Some(ys("2026-spring")));
+        assert_eq!(
+            YearSeason::at(time, parse_utc_offset("UTC").unwrap()),
+            Some(ys("2026-winter"))
+        );
+
+        let seasons: Vec<_> = ys("2026-winter").recent().collect();
+        assert_eq!(
+            seasons,
+            vec![ys("2025-fall"), ys("2026-winter"), ys("2026-spring")]
+        );
+        assert_eq!(ys("0-winter").recent().count(), 2);
+    }
+
+    #[test]
+    fn test_parse_utc_offset() {
+        let offset = |s| parse_utc_offset(s).map(|o| o.local_minus_utc());
+        assert_eq!(offset("+09:00"), Ok(9 * 3600));
+        assert_eq!(offset("-0530"), Ok(-(5 * 3600 + 30 * 60)));
+        assert_eq!(offset("UTC"), Ok(0));
+
+        assert!(parse_utc_offset("JST").is_err());
+        assert!(parse_utc_offset("+9").is_err());
+        assert!(parse_utc_offset("+09:60").is_err());
+        assert!(parse_utc_offset("+25:00").is_err());
+    }
+
+    #[test]
+    fn test_release_file() {
+        let path = ys("2026-winter").release_file(Path::new("release"));
+        assert_eq!(path, Path::new("release/2026/2026-winter-mal.json"));
+        assert_eq!(
+            YearSeason::from_release_file(&path),
+            Some(ys("2026-winter"))
+        );
+        assert_eq!(
+            YearSeason::from_release_file(Path::new("release/README.md")),
+            None
+        );
+
+        let enriched = ys("2026-winter").enriched_file(Path::new("release"));
+        assert_eq!(
+            enriched,
+            Path::new("release/2026/2026-winter-enriched.json")
+        );
+        assert_eq!(YearSeason::from_release_file(&enriched), None);
+        let calendar = ys("2026-winter").calendar_file(Path::new("release"));
+        assert_eq!(
+            calendar,
+            Path::new("release/2026/2026-winter-calendar.json")
+        );
+        assert_eq!(YearSeason::from_release_file(&calendar), None);
+    }
+}
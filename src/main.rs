@@ -1,13 +1,21 @@
-mod core;
-mod services;
+mod cli;
 
 use anyhow::{Context, Result};
-use core::SeasonProcessor;
-use services::bgmtv::BgmtvClient;
-use services::ds::DsClient;
-use services::mal::{MalClient, Season};
-use std::path::PathBuf;
-use tracing::info;
+use cli::{Args, Command, EvalArgs, RematchArgs, VerifyArgs};
+use season_data::core::{
+    Consensus, DEFAULT_AGENT_MAX_TURNS, DEFAULT_SEASON_TIMEZONE, DateTolerance, EvalReport,
+    MatchAgent, SeasonHistory, SeasonProcessor, SeasonRange, YearSeason, load_ground_truth,
+    parse_utc_offset, sample,
+};
+use season_data::services::bgmtv::{BgmtvClient, SortOrder};
+use season_data::services::cache::{CacheMode, ResponseCache};
+use season_data::services::llm::{LlmClient, LlmConfig, LlmPricing, PromptContext, PromptTemplate};
+use season_data::services::mal::{MalClient, Season};
+use std::path::Path;
+use tracing::{info, warn};
+
+/// 默认的 LLM 判断缓存目录
+const DEFAULT_DECISION_CACHE: &str = ".cache/llm-decisions";
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -20,43 +28,413 @@ async fn main() -> Result<()> {
         )
         .init();
 
-    let bgm_token = std::env::var("BGM_TOKEN").context("BGM_TOKEN must be set in .env")?;
-    let mal_client_id =
-        std::env::var("MAL_CLIENT_ID").context("MAL_CLIENT_ID must be set in .env")?;
-    let ds_api_key = std::env::var("DS_API_KEY").ok();
+    let args = Args::parse()?;
+
+    // 评估时使用响应缓存；重放模式不发送请求，因此不要求 API 凭据
+    let cache = match &args.command {
+        Command::Update(_) | Command::Rematch(_) => None,
+        // 核对需要较新的条目：只读取有效期以内的记录，中断后重新运行时跳过已获取的条目
+        Command::Verify(verify) => verify.cache_dir.as_ref().map(|dir| {
+            info!(
+                dir = %dir.display(),
+                max_age_secs = verify.cache_max_age.as_secs(),
+                "API 响应缓存"
+            );
+            ResponseCache::new(dir, CacheMode::Record).with_max_age(verify.cache_max_age)
+        }),
+        Command::Eval(eval) => {
+            let mode = if eval.replay {
+                CacheMode::Replay
+            } else {
+                CacheMode::Record
+            };
+            info!(dir = %eval.cache_dir.display(), mode = ?mode, "API 响应缓存");
+            Some(ResponseCache::new(&eval.cache_dir, mode))
+        }
+    };
+
+    // 核对只请求 Bangumi 公开 API，不要求凭据
+    let require_credentials = matches!(args.command, Command::Update(_) | Command::Rematch(_));
+    let (mut bgm_client, mal_client) = match &cache {
+        None if require_credentials => {
+            let bgm_token = std::env::var("BGM_TOKEN").context("BGM_TOKEN must be set in .env")?;
+            let mal_client_id =
+                std::env::var("MAL_CLIENT_ID").context("MAL_CLIENT_ID must be set in .env")?;
+            (BgmtvClient::new(bgm_token), MalClient::new(mal_client_id))
+        }
+        _ => {
+            let bgm_client = match std::env::var("BGM_TOKEN") {
+                Ok(token) => BgmtvClient::new(token),
+                Err(_) => BgmtvClient::anonymous(),
+            };
+            let mal_client = MalClient::new(std::env::var("MAL_CLIENT_ID").unwrap_or_default());
+            match &cache {
+                Some(cache) => (
+                    bgm_client.with_cache(cache.clone()),
+                    mal_client.with_cache(cache.clone()),
+                ),
+                None => (bgm_client, mal_client),
+            }
+        }
+    };
+    // 每种排序最多取的搜索结果数（默认一页以内，超过一页时翻页）
+    if let Some(limit) = env_count("BGM_SEARCH_LIMIT")? {
+        info!(limit = limit, "Bangumi 搜索结果上限");
+        bgm_client = bgm_client.with_search_limit(limit);
+    }
+    // 匹配度排序之后追加的排序，如 `heat,rank`
+    if let Ok(sorts) = std::env::var("BGM_SEARCH_SORTS") {
+        let sorts = sorts
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<SortOrder>().map_err(anyhow::Error::msg))
+            .collect::<Result<Vec<_>>>()?;
+        info!(sorts = ?sorts, "Bangumi 追加搜索排序");
+        bgm_client = bgm_client.with_secondary_sorts(sorts);
+    }
+    let llm_config = LlmConfig::from_env()?;
 
-    let bgm_client = BgmtvClient::new(bgm_token);
-    let mal_client = MalClient::new(mal_client_id);
+    let mut processor =
+        SeasonProcessor::new(mal_client, bgm_client).with_carry_over(args.carry_over);
+    // 限制日期搜索的容差（逐级放宽），如 `2w,3m,1y`；为空时直接不限日期搜索
+    if let Ok(tolerances) = std::env::var("BGM_DATE_TOLERANCE") {
+        let tolerances = tolerances
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.parse::<DateTolerance>().map_err(anyhow::Error::msg))
+            .collect::<Result<Vec<_>>>()?;
+        info!(tolerances = ?tolerances, "Bangumi 搜索日期容差");
+        processor = processor.with_date_tolerances(tolerances);
+    }
+    // 由前作映射推断续作（默认关闭，设为 `on` 启用）
+    if std::env::var("BGM_RELATION_WALK").is_ok_and(|v| v == "on") {
+        info!("启用续作推断");
+        processor = processor.with_relation_walk(true);
+    }
+    if let Some(config) = llm_config {
+        info!(
+            provider = %config.provider,
+            base_url = %config.base_url,
+            model = %config.model,
+            "LLM 客户端已启用"
+        );
+        let pricing = LlmPricing::from_env();
+        let consensus_config = config.consensus_from_env();
+        let mut llm_client = LlmClient::new(config).with_pricing(pricing);
+        if let Some(cache) = &cache {
+            llm_client = llm_client.with_cache(cache.clone());
+        }
+        let prompt_context = match std::env::var("LLM_PROMPT_CONTEXT") {
+            Ok(context) => {
+                let context: PromptContext = context.parse().map_err(anyhow::Error::msg)?;
+                info!(context = ?context, "LLM 提示上下文");
+                Some(context)
+            }
+            Err(_) => None,
+        };
+        if let Some(context) = prompt_context {
+            llm_client = llm_client.with_prompt_context(context);
+        }
+        let template = match std::env::var("LLM_PROMPT_FILE") {
+            Ok(path) => PromptTemplate::from_file(&path)?,
+            Err(_) => PromptTemplate::default(),
+        };
+        info!(version = %template.version(), "LLM 提示模板");
+        llm_client = llm_client.with_prompt_template(template.clone());
+        // 判断缓存：输入不变的条目复用上次的判断（评估时不使用，以免掩盖提示改动）
+        let decision_dir = std::env::var("LLM_DECISION_CACHE")
+            .unwrap_or_else(|_| DEFAULT_DECISION_CACHE.to_string());
+        if cache.is_none() && decision_dir != "off" {
+            info!(dir = %decision_dir, refresh = args.llm_refresh, "LLM 判断缓存");
+            llm_client = llm_client
+                .with_decision_cache(ResponseCache::new(&decision_dir, CacheMode::Record))
+                .with_decision_refresh(args.llm_refresh);
+        }
+        if let Some(budget) = args.llm_budget {
+            if !pricing.is_set() {
+                warn!("未配置 LLM_PRICE_*，费用按 0 估算，预算不会生效");
+            }
+            info!(budget = budget, "LLM 预算");
+            llm_client = llm_client.with_budget(budget);
+        }
 
-    let mut processor = SeasonProcessor::new(mal_client, bgm_client);
-    if let Some(api_key) = ds_api_key {
-        info!("DeepSeek 客户端已启用");
-        processor = processor.with_ds_client(DsClient::new(api_key));
+        // 双判定共识模式
+        match std::env::var("LLM_CONSENSUS").ok().as_deref() {
+            Some("shuffle") => {
+                info!("LLM 共识模式：候选打乱顺序再判断一次");
+                processor = processor.with_consensus(Consensus::ShuffledCandidates);
+            }
+            Some("model") => {
+                let config =
+                    consensus_config.context("LLM_CONSENSUS=model 需要设置 LLM_CONSENSUS_MODEL")?;
+                info!(
+                    provider = %config.provider,
+                    model = %config.model,
+                    "LLM 共识模式：第二个模型再判断一次"
+                );
+                // 第二个模型的价格单独配置，两次判断使用相同的提示上下文与模板
+                let second_pricing = LlmPricing::consensus_from_env();
+                if args.llm_budget.is_some() && !second_pricing.is_set() {
+                    warn!("未配置 LLM_CONSENSUS_PRICE_*，第二个模型的费用按 0 估算");
+                }
+                let mut second = LlmClient::new(config)
+                    .with_pricing(second_pricing)
+                    .with_prompt_template(template)
+                    .sharing_usage_with(&llm_client);
+                if let Some(context) = prompt_context {
+                    second = second.with_prompt_context(context);
+                }
+                if let Some(cache) = &cache {
+                    second = second.with_cache(cache.clone());
+                }
+                if let Some(budget) = args.llm_budget {
+                    second = second.with_budget(budget);
+                }
+                processor = processor.with_consensus(Consensus::SecondModel(Box::new(second)));
+            }
+            Some(other) => anyhow::bail!("未知的 LLM_CONSENSUS: {}", other),
+            None => {}
+        }
+
+        processor = processor.with_llm_client(llm_client);
+
+        if let Some(batch_size) = env_count("LLM_BATCH_SIZE")? {
+            info!(batch_size = batch_size, "LLM 批量匹配");
+            processor = processor.with_llm_batch_size(batch_size);
+        }
+
+        // 工具调用匹配：模型判断无匹配或搜索无结果时，由模型自行搜索
+        if std::env::var("LLM_AGENT").is_ok_and(|v| v == "1" || v == "true") {
+            let max_turns = env_count("LLM_AGENT_MAX_TURNS")?.unwrap_or(DEFAULT_AGENT_MAX_TURNS);
+            info!(max_turns = max_turns, "LLM 工具调用匹配");
+            processor = processor.with_agent(MatchAgent::new(max_turns));
+        }
     } else {
-        info!("DeepSeek 客户端未配置，跳过模型匹配");
+        info!("LLM 客户端未配置，跳过模型匹配");
     }
 
-    // 处理 2026 年冬季
-    for year in 2020..=2025 {
-        for &season in vec![Season::Winter, Season::Spring, Season::Summer, Season::Fall].iter() {
-            let output_path =
-                PathBuf::from(format!("release/{}/{}-{}-mal.json", year, year, season));
+    match &args.command {
+        Command::Update(update_args) => {
+            let seasons = if args.recent {
+                recent_seasons()?
+            } else {
+                // 未指定时更新 2020 ~ 2025 年的全部季度
+                args.seasons.clone().unwrap_or_else(|| {
+                    YearSeason::new(2020, Season::Winter)
+                        .through(YearSeason::new(2025, Season::Fall))
+                })
+            };
+            update(
+                &processor,
+                &update_args.release_dir,
+                seasons,
+                args.enrich,
+                args.calendar,
+            )
+            .await?
+        }
+        Command::Eval(eval) => run_eval(&processor, eval).await?,
+        Command::Rematch(rematch) => run_rematch(&processor, rematch).await?,
+        Command::Verify(verify) => run_verify(&processor, verify).await?,
+    }
 
-            info!(year = year, season = %season, "开始处理季度番组");
+    if let Some(llm) = processor.llm_client() {
+        let stats = llm.usage_stats();
+        info!(
+            requests = stats.requests,
+            prompt_tokens = stats.prompt_tokens,
+            completion_tokens = stats.completion_tokens,
+            cache_hit_tokens = stats.cache_hit_tokens,
+            cache_miss_tokens = stats.cache_miss_tokens,
+            cache_hit_ratio = ?stats.cache_hit_ratio(),
+            cost = format!("{:.4}", stats.cost),
+            budget_exhausted = llm.budget_exhausted(),
+            "LLM 用量汇总"
+        );
+    }
+    if processor.consensus().is_some() {
+        let stats = processor.consensus_stats();
+        info!(
+            agreed = stats.agreed,
+            disagreed = stats.disagreed,
+            failed = stats.failed,
+            agreement_rate = ?stats.agreement_rate(),
+            "LLM 共识汇总"
+        );
+    }
 
-            let result = processor.process(year, season, &output_path).await?;
+    Ok(())
+}
 
-            info!(
-                total = result.items.len(),
-                confirmed = result
-                    .items
-                    .iter()
-                    .filter(|i| i.status.is_confirmed())
-                    .count(),
-                "处理完成"
-            );
+/// 读取正整数环境变量（未设置时为 None，不是正整数时报错）
+fn env_count(name: &str) -> Result<Option<usize>> {
+    let Ok(value) = std::env::var(name) else {
+        return Ok(None);
+    };
+    match value.trim().parse::<usize>() {
+        Ok(count) if count > 0 => Ok(Some(count)),
+        _ => anyhow::bail!("{} 不是有效的正整数: {}", name, value),
+    }
+}
+
+/// 按当前日期得到当前季度（时区由 `SEASON_TIMEZONE` 配置，默认日本标准时间）
+fn current_season() -> Result<YearSeason> {
+    let timezone =
+        std::env::var("SEASON_TIMEZONE").unwrap_or_else(|_| DEFAULT_SEASON_TIMEZONE.to_string());
+    let offset = parse_utc_offset(&timezone).map_err(anyhow::Error::msg)?;
+    let current = YearSeason::at(chrono::Utc::now(), offset).context("无法确定当前季度")?;
+    info!(timezone = %offset, current = %current, "当前季度");
+    Ok(current)
+}
+
+/// 按当前日期得到最近的季度
+fn recent_seasons() -> Result<SeasonRange> {
+    let current = current_season()?;
+    info!(current = %current, "按当前日期更新最近季度");
+    Ok(current.recent())
+}
+
+/// 更新季度数据
+async fn update(
+    processor: &SeasonProcessor,
+    release_dir: &Path,
+    seasons: SeasonRange,
+    enrich: bool,
+    calendar: bool,
+) -> Result<()> {
+    // 每日放送只对应当前季度
+    let calendar_season = if calendar {
+        Some(current_season()?)
+    } else {
+        None
+    };
+    let mut calendar_checked = false;
+    // 之前各季度的发布数据只在运行开始时读取一次
+    let mut history = SeasonHistory::load(release_dir).await?;
+    for season in seasons {
+        info!(season = %season, "开始处理季度番组");
+
+        let mut result = processor.process(season, release_dir, &mut history).await?;
+
+        info!(
+            total = result.items.len(),
+            confirmed = result
+                .items
+                .iter()
+                .filter(|i| i.status.is_confirmed())
+                .count(),
+            "处理完成"
+        );
+
+        if enrich {
+            let enriched = processor.enrich(&result).await;
+            let path = season.enriched_file(release_dir);
+            enriched.save(&path).await?;
+            info!(path = %path.display(), count = enriched.items.len(), "已写入条目详情");
+        }
+
+        if calendar_season == Some(season) {
+            let report = processor.cross_check_calendar(&mut result).await?;
+            if report.proposed > 0 {
+                result.save(&season.release_file(release_dir)).await?;
+            }
+            let path = season.calendar_file(release_dir);
+            report.save(&path).await?;
+            info!(path = %path.display(), "已写入每日放送对照");
+            calendar_checked = true;
         }
     }
+    if let Some(current) = calendar_season
+        && !calendar_checked
+    {
+        warn!(current = %current, "更新范围不含当前季度，跳过每日放送对照");
+    }
+
+    Ok(())
+}
 
+/// 以发布数据中已确认的条目为真值评估匹配流程
+async fn run_eval(processor: &SeasonProcessor, args: &EvalArgs) -> Result<()> {
+    let cases = load_ground_truth(&args.release_dir, &args.statuses).await?;
+    info!(
+        total = cases.len(),
+        sample = args.sample,
+        seed = args.seed,
+        "读取评估真值"
+    );
+    let cases = sample(cases, args.sample, args.seed);
+
+    let outcomes = processor.replay(&args.release_dir, &cases).await?;
+    let report = EvalReport::from_outcomes(&outcomes);
+    println!("{}", report);
+
+    if let Some(path) = &args.output {
+        report
+            .save(path)
+            .await
+            .with_context(|| format!("写入评估报告失败: {}", path.display()))?;
+        info!(path = %path.display(), "已保存评估报告");
+    }
+    Ok(())
+}
+
+/// 重跑由旧版本提示模板判断的条目
+async fn run_rematch(processor: &SeasonProcessor, args: &RematchArgs) -> Result<()> {
+    let llm = processor
+        .llm_client()
+        .context("rematch 需要配置 LLM（LLM_BASE_URL 或 LLM_API_KEY）")?;
+    info!(
+        release = %args.release_dir.display(),
+        version = %llm.prompt_template().version(),
+        "开始重跑旧版本提示的判断"
+    );
+
+    let stats = processor.rematch(&args.release_dir).await?;
+    info!(
+        outdated = stats.outdated,
+        updated = stats.updated,
+        kept = stats.kept,
+        "重跑完成"
+    );
+    Ok(())
+}
+
+/// 核对已确认映射的 Bangumi 条目
+async fn run_verify(processor: &SeasonProcessor, args: &VerifyArgs) -> Result<()> {
+    info!(
+        release = %args.release_dir.display(),
+        reset = args.reset,
+        "开始核对已确认映射"
+    );
+
+    let report = processor
+        .verify(&args.release_dir, args.concurrency, args.reset)
+        .await?;
+    for finding in &report.findings {
+        warn!(
+            season = %finding.season,
+            mal_id = finding.mal_id,
+            mal_title = %finding.mal_title,
+            bgm_id = finding.bgm_id,
+            issue = ?finding.issue,
+            "映射的 Bangumi 条目有问题"
+        );
+    }
+    info!(
+        checked = report.checked,
+        failed = report.failed,
+        findings = report.findings.len(),
+        reset = report.reset,
+        "核对完成"
+    );
+    if !args.reset && !report.findings.is_empty() {
+        info!("使用 --reset 将这些条目重置为未确认，下次更新时重新匹配");
+    }
+
+    if let Some(path) = &args.output {
+        report.save(path).await?;
+        info!(path = %path.display(), "已写入核对报告");
+    }
     Ok(())
 }
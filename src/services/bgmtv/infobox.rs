@@ -0,0 +1,277 @@
+use super::InfoboxItem;
+use chrono::Weekday;
+use serde_json::Value;
+
+/// 解析后的信息框字段
+///
+/// 信息框的值可能是字符串，也可能是 `{k, v}` 对象列表（如别名）；同一个键可能出现多次
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Infobox {
+    /// 中文名
+    pub name_cn: Option<String>,
+    /// 别名
+    pub aliases: Vec<String>,
+    /// 话数
+    pub episodes: Option<u32>,
+    /// 放送开始，格式与 MAL 相同：`2023-09-29`、`2023-10`、`2023`
+    pub air_date: Option<String>,
+    /// 放送星期
+    pub air_weekday: Option<Weekday>,
+    /// 动画制作
+    pub studios: Vec<String>,
+    /// 导演
+    pub directors: Vec<String>,
+    /// 官方网站
+    pub website: Option<String>,
+}
+
+impl Infobox {
+    pub fn parse(items: &[InfoboxItem]) -> Self {
+        let mut infobox = Infobox::default();
+        for item in items {
+            let values = values(&item.value);
+            let first = || values.first().cloned();
+            match item.key.trim() {
+                "中文名" => infobox.name_cn = infobox.name_cn.take().or_else(first),
+                "别名" => infobox.aliases.extend(values),
+                "话数" => {
+                    infobox.episodes = infobox
+                        .episodes
+                        .or_else(|| values.iter().find_map(|v| parse_episodes(v)))
+                }
+                "放送开始" => {
+                    infobox.air_date = infobox
+                        .air_date
+                        .take()
+                        .or_else(|| values.iter().find_map(|v| parse_date(v)))
+                }
+                "放送星期" => {
+                    infobox.air_weekday = infobox
+                        .air_weekday
+                        .or_else(|| values.iter().find_map(|v| parse_weekday(v)))
+                }
+                "动画制作" => infobox
+                    .studios
+                    .extend(values.iter().flat_map(|v| split_names(v))),
+                "导演" => infobox
+                    .directors
+                    .extend(values.iter().flat_map(|v| split_names(v))),
+                "官方网站" => infobox.website = infobox.website.take().or_else(first),
+                _ => {}
+            }
+        }
+        dedup(&mut infobox.aliases);
+        dedup(&mut infobox.studios);
+        dedup(&mut infobox.directors);
+        infobox
+    }
+}
+
+/// 信息框的值（字符串或 `{k, v}` 列表），去掉空白与空值
+fn values(value: &Value) -> Vec<String> {
+    let texts: Vec<&str> = match value {
+        Value::String(s) => vec![s],
+        Value::Array(list) => list
+            .iter()
+            .filter_map(|entry| match entry {
+                Value::String(s) => Some(s.as_str()),
+                _ => entry.get("v")?.as_str(),
+            })
+            .collect(),
+        _ => vec![],
+    };
+    texts
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 拆分多个名称：`A、B`、`A / B`、`A，B`
+fn split_names(value: &str) -> Vec<String> {
+    value
+        .split(['、', '/', '／', '，', ',', '&', '＆'])
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn dedup(list: &mut Vec<String>) {
+    let mut seen = std::collections::HashSet::new();
+    list.retain(|s| seen.insert(s.clone()));
+}
+
+/// 话数取开头的数字（`12`、`12+1`、`全12话`），`未知` 等为 None
+fn parse_episodes(value: &str) -> Option<u32> {
+    let digits: String = value
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok().filter(|&n| n > 0)
+}
+
+/// 放送开始：`2023年9月29日`、`2023-09-29`、`2023/9/29`、`2023年10月`、`2023年`
+///
+/// 后面可能带有地区或说明（如 `2023年9月29日（日本）`），只取开头的日期
+fn parse_date(value: &str) -> Option<String> {
+    let mut numbers = Vec::new();
+    let mut current = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            current.push(c);
+            continue;
+        }
+        if !current.is_empty() {
+            numbers.push(std::mem::take(&mut current));
+        }
+        let separator = matches!(c, '年' | '月' | '日' | '-' | '/' | '.');
+        if !separator || numbers.len() == 3 {
+            break;
+        }
+    }
+    if !current.is_empty() {
+        numbers.push(current);
+    }
+
+    let parse = |i: usize| numbers.get(i).and_then(|s| s.parse::<u32>().ok());
+    let year = parse(0).filter(|y| (1900..=9999).contains(y))?;
+    match (parse(1), parse(2)) {
+        (Some(month), Some(day)) => {
+            chrono::NaiveDate::from_ymd_opt(year as i32, month, day)?;
+            Some(format!("{:04}-{:02}-{:02}", year, month, day))
+        }
+        (Some(month), None) if (1..=12).contains(&month) => {
+            Some(format!("{:04}-{:02}", year, month))
+        }
+        (None, _) => Some(format!("{:04}", year)),
+        _ => None,
+    }
+}
+
+/// 放送星期：`星期五`、`周五`、`金曜日`、`Friday`
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    let value = value.trim();
+    let day = value
+        .strip_prefix("星期")
+        .or_else(|| value.strip_prefix("周"))
+        .or_else(|| value.strip_prefix("週"))
+        .unwrap_or(value);
+    match day.chars().next()? {
+        '一' | '月' => Some(Weekday::Mon),
+        '二' | '火' => Some(Weekday::Tue),
+        '三' | '水' => Some(Weekday::Wed),
+        '四' | '木' => Some(Weekday::Thu),
+        '五' | '金' => Some(Weekday::Fri),
+        '六' | '土' => Some(Weekday::Sat),
+        '日' | '天' => Some(Weekday::Sun),
+        _ => day.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::bgmtv::Subject;
+
+    fn infobox(json: Value) -> Infobox {
+        let items: Vec<InfoboxItem> = serde_json::from_value(json).unwrap();
+        Infobox::parse(&items)
+    }
+
+    #[test]
+    fn test_parse_subject_infobox() {
+        // 葬送のフリーレン（400602）的信息框
+        let subject: Subject = serde_json::from_value(serde_json::json!({
+            "id": 400602,
+            "type": 2,
+            "name": "葬送のフリーレン",
+            "infobox": [
+                { "key": "中文名", "value": "葬送的芙莉莲" },
+                { "key": "别名", "value": [
+                    { "v": "Frieren: Beyond Journey's End" },
+                    { "v": "Sousou no Frieren" },
+                    { "k": "英文", "v": "Frieren" },
+                    { "v": "" }
+                ] },
+                { "key": "话数", "value": "28" },
+                { "key": "放送开始", "value": "2023年9月29日" },
+                { "key": "放送星期", "value": "星期五" },
+                { "key": "官方网站", "value": "https://frieren-anime.jp/" },
+                { "key": "播放电视台", "value": "日本テレビ" },
+                { "key": "原作", "value": "山田鐘人・アベツカサ（小学館「週刊少年サンデー」連載）" },
+                { "key": "导演", "value": "斎藤圭一郎" },
+                { "key": "动画制作", "value": "マッドハウス" }
+            ]
+        }))
+        .unwrap();
+
+        let infobox = subject.infobox_fields();
+        assert_eq!(infobox.name_cn.as_deref(), Some("葬送的芙莉莲"));
+        assert_eq!(
+            infobox.aliases,
+            vec![
+                "Frieren: Beyond Journey's End",
+                "Sousou no Frieren",
+                "Frieren"
+            ]
+        );
+        assert_eq!(infobox.episodes, Some(28));
+        assert_eq!(infobox.air_date.as_deref(), Some("2023-09-29"));
+        assert_eq!(infobox.air_weekday, Some(Weekday::Fri));
+        assert_eq!(infobox.studios, vec!["マッドハウス"]);
+        assert_eq!(infobox.directors, vec!["斎藤圭一郎"]);
+        assert_eq!(
+            infobox.website.as_deref(),
+            Some("https://frieren-anime.jp/")
+        );
+    }
+
+    #[test]
+    fn test_parse_list_values_and_repeated_keys() {
+        let infobox = infobox(serde_json::json!([
+            { "key": "别名", "value": "SPY×FAMILY 第2期" },
+            { "key": "别名", "value": [{ "v": "间谍过家家 第二季" }, { "v": "SPY×FAMILY 第2期" }] },
+            { "key": "动画制作", "value": "WIT STUDIO、CloverWorks" },
+            { "key": "动画制作", "value": [{ "v": "CloverWorks" }] },
+            { "key": "导演", "value": "古橋一浩 / 片桐崇" },
+            { "key": "话数", "value": "12+1" },
+            { "key": "放送开始", "value": "2023年10月7日（日本）" },
+            { "key": "放送星期", "value": "周六" }
+        ]));
+
+        assert_eq!(
+            infobox.aliases,
+            vec!["SPY×FAMILY 第2期", "间谍过家家 第二季"]
+        );
+        assert_eq!(infobox.studios, vec!["WIT STUDIO", "CloverWorks"]);
+        assert_eq!(infobox.directors, vec!["古橋一浩", "片桐崇"]);
+        assert_eq!(infobox.episodes, Some(12));
+        assert_eq!(infobox.air_date.as_deref(), Some("2023-10-07"));
+        assert_eq!(infobox.air_weekday, Some(Weekday::Sat));
+        assert_eq!(infobox.name_cn, None);
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        assert_eq!(parse_date("2024-01-05").as_deref(), Some("2024-01-05"));
+        assert_eq!(parse_date("2024/1/5").as_deref(), Some("2024-01-05"));
+        assert_eq!(parse_date("2024年10月").as_deref(), Some("2024-10"));
+        assert_eq!(parse_date("2024年").as_deref(), Some("2024"));
+        assert_eq!(parse_date("2024年2月30日"), None);
+        assert_eq!(parse_date("未定"), None);
+
+        assert_eq!(parse_episodes("全12话"), Some(12));
+        assert_eq!(parse_episodes("未知"), None);
+        assert_eq!(parse_episodes("0"), None);
+
+        assert_eq!(parse_weekday("金曜日"), Some(Weekday::Fri));
+        assert_eq!(parse_weekday("星期日"), Some(Weekday::Sun));
+        assert_eq!(parse_weekday("Monday"), Some(Weekday::Mon));
+        assert_eq!(parse_weekday("不定"), None);
+
+        // 没有信息框
+        assert_eq!(Infobox::parse(&[]), Infobox::default());
+    }
+}
@@ -1,13 +1,35 @@
-use reqwest::Client;
+mod infobox;
+
+pub use infobox::Infobox;
+
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
+use tokio::task::JoinSet;
 use tracing::warn;
 
+use crate::services::cache::{CacheError, ResponseCache};
+
 const BASE_URL: &str = "https://api.bgm.tv";
 const USER_AGENT: &str = "bgm-rank/season-data (https://github.com/bgm-rank/season-data)";
 const MAX_RETRIES: u32 = 3;
 const RETRY_DELAY_MS: u64 = 1000;
+const CACHE_NAMESPACE: &str = "bgmtv";
+/// 搜索分页时每页的最大数量
+const SEARCH_PAGE_SIZE: usize = 20;
+
+/// 每种排序最多取的搜索结果数
+///
+/// 不超过一页，默认不翻页；翻页需通过 [`BgmtvClient::with_search_limit`] 设置超过
+/// 一页（20 条）的上限
+pub const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// 关联条目的关系：续集
+pub const RELATION_SEQUEL: &str = "续集";
 
 #[derive(Error, Debug)]
 pub enum BgmtvError {
@@ -15,6 +37,13 @@ pub enum BgmtvError {
     Request(#[from] reqwest::Error),
     #[error("API error: {0}")]
     Api(String),
+    /// 条目不存在（HTTP 404，如已删除）
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
 }
 
 /// 条目类型
@@ -158,6 +187,66 @@ pub struct Subject {
     pub tags: Option<Vec<Tag>>,
 }
 
+/// 关联条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedSubject {
+    pub id: u64,
+    #[serde(rename = "type")]
+    pub subject_type: u8,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub name_cn: Option<String>,
+    /// 关系（`续集`、`前传`、`番外篇` 等）
+    pub relation: String,
+}
+
+/// 每日放送的星期
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarWeekday {
+    /// 1 ~ 7（星期一 ~ 星期日）
+    pub id: u8,
+    #[serde(default)]
+    pub en: String,
+    #[serde(default)]
+    pub cn: String,
+    #[serde(default)]
+    pub ja: String,
+}
+
+/// 每日放送中的条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarSubject {
+    pub id: u64,
+    #[serde(rename = "type", default)]
+    pub subject_type: Option<u8>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub name_cn: Option<String>,
+    #[serde(default)]
+    pub air_date: Option<String>,
+    #[serde(default)]
+    pub air_weekday: Option<u8>,
+    #[serde(default)]
+    pub images: Option<Images>,
+}
+
+/// 每日放送中的一天
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarDay {
+    pub weekday: CalendarWeekday,
+    #[serde(default)]
+    pub items: Vec<CalendarSubject>,
+}
+
+impl Subject {
+    /// 解析信息框中的常用字段
+    pub fn infobox_fields(&self) -> Infobox {
+        Infobox::parse(self.infobox.as_deref().unwrap_or_default())
+    }
+}
+
 /// 搜索筛选器
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchFilter {
@@ -203,18 +292,27 @@ impl SearchFilter {
 }
 
 /// 排序规则
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SortOrder {
+    #[default]
     Match,
     Heat,
     Rank,
     Score,
 }
 
-impl Default for SortOrder {
-    fn default() -> Self {
-        Self::Match
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "match" => Ok(SortOrder::Match),
+            "heat" => Ok(SortOrder::Heat),
+            "rank" => Ok(SortOrder::Rank),
+            "score" => Ok(SortOrder::Score),
+            _ => Err(format!("unknown sort order: {}", s)),
+        }
     }
 }
 
@@ -241,6 +339,11 @@ impl SearchRequest {
         self.filter = Some(filter);
         self
     }
+
+    pub fn with_sort(mut self, sort: SortOrder) -> Self {
+        self.sort = sort;
+        self
+    }
 }
 
 /// 分页条目响应
@@ -252,9 +355,26 @@ pub struct PagedSubject {
     pub data: Vec<Subject>,
 }
 
+/// 是否还需要请求下一页
+///
+/// `offset` 为已返回的结果数（含重复），`received` 为本页返回的数量（为 0 时说明没有更多结果，
+/// 避免 `total` 与实际结果不一致时一直翻页），`collected` 为去重后的数量，`cap` 为上限
+fn has_next_page(offset: usize, total: u32, received: usize, collected: usize, cap: usize) -> bool {
+    received > 0 && offset < total as usize && collected < cap
+}
+
+/// 按 ID 去重合并搜索结果，保持先出现的顺序
+fn merge_subjects(subjects: &mut Vec<Subject>, seen: &mut HashSet<u64>, page: Vec<Subject>) {
+    subjects.extend(page.into_iter().filter(|s| seen.insert(s.id)));
+}
+
+#[derive(Clone)]
 pub struct BgmtvClient {
     client: Client,
     access_token: Option<String>,
+    cache: Option<ResponseCache>,
+    search_limit: usize,
+    secondary_sorts: Vec<SortOrder>,
 }
 
 impl BgmtvClient {
@@ -262,37 +382,63 @@ impl BgmtvClient {
         Self {
             client: Client::new(),
             access_token: Some(access_token.into()),
+            cache: None,
+            search_limit: DEFAULT_SEARCH_LIMIT,
+            secondary_sorts: Vec::new(),
         }
     }
 
-    /// 搜索条目（带重试逻辑）
+    /// 不带 Access Token 的客户端（公开 API 可用）
+    pub fn anonymous() -> Self {
+        Self {
+            client: Client::new(),
+            access_token: None,
+            cache: None,
+            search_limit: DEFAULT_SEARCH_LIMIT,
+            secondary_sorts: Vec::new(),
+        }
+    }
+
+    /// 设置响应缓存（记录或重放 API 响应）
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// 设置每种排序最多取的搜索结果数（超过一页时翻页）
+    pub fn with_search_limit(mut self, limit: usize) -> Self {
+        self.search_limit = limit.max(1);
+        self
+    }
+
+    /// 设置匹配度排序之后追加的排序（如 `heat`、`rank`），结果按 ID 去重合并
+    pub fn with_secondary_sorts(mut self, sorts: Vec<SortOrder>) -> Self {
+        self.secondary_sorts = sorts
+            .into_iter()
+            .filter(|&sort| sort != SortOrder::Match)
+            .collect();
+        self
+    }
+
+    /// 发送请求并解析 JSON 响应（带重试逻辑）
     ///
-    /// POST /v0/search/subjects
-    pub async fn search_subjects(
+    /// `build` 每次重试都会重新构建请求，User-Agent 与认证头在此统一添加。
+    /// 设置了缓存时先按 `cache_key` 查找，成功的响应会被记录
+    async fn send_json<T: DeserializeOwned>(
         &self,
-        request: &SearchRequest,
-        limit: Option<u32>,
-        offset: Option<u32>,
-    ) -> Result<PagedSubject, BgmtvError> {
-        let url = format!("{}/v0/search/subjects", BASE_URL);
-
-        let mut query_params = Vec::new();
-        if let Some(limit) = limit {
-            query_params.push(("limit", limit.to_string()));
-        }
-        if let Some(offset) = offset {
-            query_params.push(("offset", offset.to_string()));
+        cache_key: &[&str],
+        build: impl Fn() -> RequestBuilder,
+    ) -> Result<T, BgmtvError> {
+        let cache_key = ResponseCache::key(cache_key);
+        if let Some(cache) = &self.cache
+            && let Some(body) = cache.get(CACHE_NAMESPACE, &cache_key).await?
+        {
+            return Ok(serde_json::from_str(&body)?);
         }
 
         let mut last_error = None;
         for attempt in 1..=MAX_RETRIES {
-            let mut req = self
-                .client
-                .post(&url)
-                .header("User-Agent", USER_AGENT)
-                .header("Content-Type", "application/json")
-                .query(&query_params)
-                .json(request);
+            let mut req = build().header("User-Agent", USER_AGENT);
 
             if let Some(token) = &self.access_token {
                 req = req.header("Authorization", format!("Bearer {}", token));
@@ -303,13 +449,27 @@ impl BgmtvClient {
                     if !response.status().is_success() {
                         let status = response.status();
                         let text = response.text().await.unwrap_or_default();
+                        if status == reqwest::StatusCode::NOT_FOUND {
+                            return Err(BgmtvError::NotFound(text));
+                        }
                         return Err(BgmtvError::Api(format!("{}: {}", status, text)));
                     }
 
-                    match response.json::<PagedSubject>().await {
-                        Ok(result) => return Ok(result),
+                    let parsed = match response.text().await {
+                        Ok(body) => serde_json::from_str::<T>(&body)
+                            .map(|result| (body, result))
+                            .map_err(BgmtvError::Json),
+                        Err(e) => Err(BgmtvError::Request(e)),
+                    };
+                    match parsed {
+                        Ok((body, result)) => {
+                            if let Some(cache) = &self.cache {
+                                cache.put(CACHE_NAMESPACE, &cache_key, &body).await;
+                            }
+                            return Ok(result);
+                        }
                         Err(e) => {
-                            last_error = Some(BgmtvError::Request(e));
+                            last_error = Some(e);
                             if attempt < MAX_RETRIES {
                                 warn!(
                                     attempt = attempt,
@@ -338,6 +498,141 @@ impl BgmtvClient {
         Err(last_error.unwrap())
     }
 
+    /// 搜索条目（带重试逻辑）
+    ///
+    /// POST /v0/search/subjects
+    pub async fn search_subjects(
+        &self,
+        request: &SearchRequest,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<PagedSubject, BgmtvError> {
+        let url = format!("{}/v0/search/subjects", BASE_URL);
+
+        let mut query_params = Vec::new();
+        if let Some(limit) = limit {
+            query_params.push(("limit", limit.to_string()));
+        }
+        if let Some(offset) = offset {
+            query_params.push(("offset", offset.to_string()));
+        }
+
+        let body = serde_json::to_string(request)?;
+        let query = serde_json::to_string(&query_params)?;
+        self.send_json(&[&url, &query, &body], || {
+            self.client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .query(&query_params)
+                .json(request)
+        })
+        .await
+    }
+
+    /// 获取条目详情（带重试逻辑）
+    ///
+    /// GET /v0/subjects/{subject_id}
+    pub async fn get_subject(&self, subject_id: u64) -> Result<Subject, BgmtvError> {
+        let url = format!("{}/v0/subjects/{}", BASE_URL, subject_id);
+        self.send_json(&[&url], || self.client.get(&url)).await
+    }
+
+    /// 并发获取多个条目详情，最多 `concurrency` 个请求同时进行，结果按 `subject_ids` 的顺序返回
+    ///
+    /// 已合并的条目会重定向到合并后的条目，返回的 `id` 与请求的不同
+    pub async fn get_subjects(
+        &self,
+        subject_ids: &[u64],
+        concurrency: usize,
+    ) -> Vec<Result<Subject, BgmtvError>> {
+        let client = Arc::new(self.clone());
+        let mut pending = subject_ids.iter().copied().enumerate();
+        let mut results: Vec<Option<Result<Subject, BgmtvError>>> =
+            subject_ids.iter().map(|_| None).collect();
+        let mut tasks = JoinSet::new();
+        loop {
+            while tasks.len() < concurrency.max(1)
+                && let Some((index, subject_id)) = pending.next()
+            {
+                let client = client.clone();
+                tasks.spawn(async move { (index, client.get_subject(subject_id).await) });
+            }
+            let Some(joined) = tasks.join_next().await else {
+                break;
+            };
+            let (index, result) = joined.expect("获取条目详情的任务异常退出");
+            results[index] = Some(result);
+        }
+        results.into_iter().flatten().collect()
+    }
+
+    /// 获取关联条目（带重试逻辑）
+    ///
+    /// GET /v0/subjects/{subject_id}/subjects
+    pub async fn get_related_subjects(
+        &self,
+        subject_id: u64,
+    ) -> Result<Vec<RelatedSubject>, BgmtvError> {
+        let url = format!("{}/v0/subjects/{}/subjects", BASE_URL, subject_id);
+        self.send_json(&[&url], || self.client.get(&url)).await
+    }
+
+    /// 翻页搜索，最多取 `cap` 条（按 ID 去重）
+    ///
+    /// 按响应的 `total` 与已返回的数量计算下一页的 `offset`。第一页不带 `offset` 参数
+    pub async fn search_all(
+        &self,
+        request: &SearchRequest,
+        cap: usize,
+    ) -> Result<Vec<Subject>, BgmtvError> {
+        let mut subjects = Vec::new();
+        let mut seen = HashSet::new();
+        let mut offset = 0;
+        loop {
+            let limit = (cap - subjects.len()).min(SEARCH_PAGE_SIZE);
+            let page = self
+                .search_subjects(
+                    request,
+                    Some(limit as u32),
+                    (offset > 0).then_some(offset as u32),
+                )
+                .await?;
+            let received = page.data.len();
+            offset += received;
+            merge_subjects(&mut subjects, &mut seen, page.data);
+            if !has_next_page(offset, page.total, received, subjects.len(), cap) {
+                break;
+            }
+        }
+        subjects.truncate(cap);
+        Ok(subjects)
+    }
+
+    /// 按匹配度与追加的排序依次搜索，合并去重（匹配度排序的结果在前）
+    async fn search_anime(
+        &self,
+        keyword: &str,
+        filter: SearchFilter,
+    ) -> Result<Vec<Subject>, BgmtvError> {
+        let request = SearchRequest::new(keyword).with_filter(filter);
+        let mut subjects = self.search_all(&request, self.search_limit).await?;
+        let mut seen: HashSet<u64> = subjects.iter().map(|s| s.id).collect();
+        for &sort in &self.secondary_sorts {
+            let request = request.clone().with_sort(sort);
+            let page = self.search_all(&request, self.search_limit).await?;
+            merge_subjects(&mut subjects, &mut seen, page);
+        }
+        Ok(subjects)
+    }
+
+    /// 获取每日放送（本周放送中的动画，按星期分组）
+    ///
+    /// GET /calendar
+    pub async fn get_calendar(&self) -> Result<Vec<CalendarDay>, BgmtvError> {
+        let url = format!("{}/calendar", BASE_URL);
+        self.send_json(&[&url], || self.client.get(&url)).await
+    }
+
     /// 按关键词搜索动画（包含 NSFW，限制日期范围）
     pub async fn search_anime_by_keyword(
         &self,
@@ -348,9 +643,7 @@ impl BgmtvClient {
         let filter = SearchFilter::anime()
             .air_date_range(start_date, end_date)
             .include_nsfw();
-        let request = SearchRequest::new(keyword).with_filter(filter);
-        let result = self.search_subjects(&request, Some(10), None).await?;
-        Ok(result.data)
+        self.search_anime(keyword, filter).await
     }
 
     /// 按关键词搜索动画（包含 NSFW，不限制日期）
@@ -358,13 +651,28 @@ impl BgmtvClient {
         &self,
         keyword: &str,
     ) -> Result<Vec<Subject>, BgmtvError> {
-        let filter = SearchFilter::anime().include_nsfw();
-        let request = SearchRequest::new(keyword).with_filter(filter);
-        let result = self.search_subjects(&request, Some(10), None).await?;
-        Ok(result.data)
+        self.search_anime(keyword, SearchFilter::anime().include_nsfw())
+            .await
     }
 }
 
+/// 记录一次关键词搜索（匹配度排序、默认上限的第一页）的响应，供重放测试使用
+#[cfg(test)]
+pub(crate) async fn record_search(
+    cache: &ResponseCache,
+    keyword: &str,
+    filter: SearchFilter,
+    subjects: serde_json::Value,
+) {
+    let url = format!("{}/v0/search/subjects", BASE_URL);
+    let limit = DEFAULT_SEARCH_LIMIT.min(SEARCH_PAGE_SIZE);
+    let query = serde_json::to_string(&[("limit", limit.to_string())]).unwrap();
+    let body = serde_json::to_string(&SearchRequest::new(keyword).with_filter(filter)).unwrap();
+    let total = subjects.as_array().map_or(0, Vec::len);
+    let page = serde_json::json!({ "total": total, "limit": limit, "offset": 0, "data": subjects });
+    let key = ResponseCache::key(&[&url, &query, &body]);
+    cache.put(CACHE_NAMESPACE, &key, &page.to_string()).await;
+}
 
 #[cfg(test)]
 mod tests {
@@ -466,6 +774,123 @@ mod tests {
         assert_eq!(response.data[0].id, 400602);
     }
 
+    #[test]
+    fn test_deserialize_related_subjects() {
+        let json = r#"[
+            {
+                "id": 400602,
+                "type": 2,
+                "name": "葬送のフリーレン 第2期",
+                "name_cn": "葬送的芙莉莲 第二季",
+                "relation": "续集",
+                "images": { "large": "", "common": "", "medium": "", "small": "", "grid": "" }
+            },
+            { "id": 1, "type": 1, "name": "葬送のフリーレン", "name_cn": "", "relation": "改编" }
+        ]"#;
+
+        let related: Vec<RelatedSubject> = serde_json::from_str(json).unwrap();
+        assert_eq!(related.len(), 2);
+        assert_eq!(related[0].relation, RELATION_SEQUEL);
+        assert_eq!(related[1].subject_type, SubjectType::Book as u8);
+    }
+
+    #[test]
+    fn test_deserialize_calendar() {
+        let json = r#"[
+            {
+                "weekday": { "en": "Fri", "cn": "星期五", "ja": "金耀日", "id": 5 },
+                "items": [
+                    {
+                        "id": 515759,
+                        "url": "http://bgm.tv/subject/515759",
+                        "type": 2,
+                        "name": "葬送のフリーレン 第2期",
+                        "name_cn": "葬送的芙莉莲 第二季",
+                        "summary": "",
+                        "air_date": "2026-01-16",
+                        "air_weekday": 5,
+                        "rating": { "total": 1234, "score": 8.1 },
+                        "rank": 189,
+                        "images": null
+                    }
+                ]
+            },
+            { "weekday": { "en": "Sat", "cn": "星期六", "ja": "土耀日", "id": 6 }, "items": [] }
+        ]"#;
+
+        let calendar: Vec<CalendarDay> = serde_json::from_str(json).unwrap();
+        assert_eq!(calendar.len(), 2);
+        assert_eq!(calendar[0].weekday.id, 5);
+        assert_eq!(calendar[0].items[0].id, 515759);
+        assert_eq!(calendar[0].items[0].air_weekday, Some(5));
+        assert!(calendar[0].items[0].images.is_none());
+        assert!(calendar[1].items.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_subjects_keeps_order() {
+        use crate::services::cache::CacheMode;
+
+        let dir = std::env::temp_dir().join(format!("season-data-bgmtv-{}", std::process::id()));
+        let record = ResponseCache::new(&dir, CacheMode::Record);
+        for id in 1..=5 {
+            let url = format!("{}/v0/subjects/{}", BASE_URL, id);
+            let body = format!(r#"{{"id":{},"type":2}}"#, id);
+            record
+                .put(CACHE_NAMESPACE, &ResponseCache::key(&[&url]), &body)
+                .await;
+        }
+
+        // 重放模式下未缓存的条目返回错误，不发送请求
+        let client =
+            BgmtvClient::anonymous().with_cache(ResponseCache::new(&dir, CacheMode::Replay));
+        let results = client.get_subjects(&[5, 3, 404, 1, 2, 4], 2).await;
+        let ids: Vec<_> = results
+            .iter()
+            .map(|r| r.as_ref().ok().map(|s| s.id))
+            .collect();
+        assert_eq!(ids, vec![Some(5), Some(3), None, Some(1), Some(2), Some(4)]);
+        assert!(client.get_subjects(&[], 2).await.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_search_pagination() {
+        // 第一页 20 条，共 45 条，上限 50：继续翻页
+        assert!(has_next_page(20, 45, 20, 20, 50));
+        // 已取完全部结果
+        assert!(!has_next_page(45, 45, 5, 45, 50));
+        // 达到上限
+        assert!(!has_next_page(20, 45, 20, 20, 20));
+        // 服务端返回空页（total 不准确）
+        assert!(!has_next_page(20, 45, 0, 20, 50));
+
+        let subject = |id| Subject {
+            id,
+            ..serde_json::from_str(r#"{"id":0,"type":2}"#).unwrap()
+        };
+        let mut subjects = vec![subject(1), subject(2)];
+        let mut seen = HashSet::from([1, 2]);
+        merge_subjects(
+            &mut subjects,
+            &mut seen,
+            vec![subject(2), subject(3), subject(1)],
+        );
+        let ids: Vec<_> = subjects.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_sort_order() {
+        assert_eq!("heat".parse::<SortOrder>(), Ok(SortOrder::Heat));
+        assert!("hot".parse::<SortOrder>().is_err());
+
+        let request = SearchRequest::new("プリキュア").with_sort(SortOrder::Rank);
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["sort"], "rank");
+    }
+
     #[test]
     fn test_filter_serialization_skips_none() {
         let filter = SearchFilter::anime();
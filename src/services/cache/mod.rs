@@ -0,0 +1,199 @@
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::fs;
+use tracing::warn;
+
+#[derive(Error, Debug)]
+pub enum CacheError {
+    #[error("Cache miss in replay mode: {namespace}/{key}")]
+    Miss { namespace: String, key: String },
+}
+
+/// 缓存模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheMode {
+    /// 命中时读取缓存，未命中时请求 API 并记录响应
+    #[default]
+    Record,
+    /// 只读取缓存，未命中视为错误（不发送任何请求）
+    Replay,
+    /// 不读取缓存，总是请求 API 并记录响应（需要最新数据时使用）
+    Write,
+}
+
+/// API 响应缓存
+///
+/// 以请求内容的 SHA-256 为键，原样保存响应正文到 `{dir}/{namespace}/{key}.json`，
+/// 用于离线重放匹配流程（评估、调试提示词）
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    mode: CacheMode,
+    max_age: Option<Duration>,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>, mode: CacheMode) -> Self {
+        Self {
+            dir: dir.into(),
+            mode,
+            max_age: None,
+        }
+    }
+
+    /// 记录模式下超过 `max_age` 的记录视为未命中，重新请求（用于中断后继续运行，又不使用过旧的响应）
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn mode(&self) -> CacheMode {
+        self.mode
+    }
+
+    /// 由请求的各组成部分（URL、查询参数、请求体）计算缓存键
+    pub fn key(parts: &[&str]) -> String {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part.as_bytes());
+            // 分隔符，避免 ["ab", "c"] 与 ["a", "bc"] 冲突
+            hasher.update([0u8]);
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    fn path(&self, namespace: &str, key: &str) -> PathBuf {
+        self.dir.join(namespace).join(format!("{}.json", key))
+    }
+
+    /// 读取缓存的响应正文
+    ///
+    /// 重放模式下未命中返回错误；记录模式下未命中（或记录已过期）返回 `Ok(None)`，由调用方请求 API；
+    /// 只写模式总是返回 `Ok(None)`
+    pub async fn get(&self, namespace: &str, key: &str) -> Result<Option<String>, CacheError> {
+        if self.mode == CacheMode::Write {
+            return Ok(None);
+        }
+        let path = self.path(namespace, key);
+        if self.mode == CacheMode::Record
+            && let Some(max_age) = self.max_age
+            && !self.is_fresh(&path, max_age).await
+        {
+            return Ok(None);
+        }
+        match fs::read_to_string(&path).await {
+            Ok(body) => Ok(Some(body)),
+            Err(_) if self.mode == CacheMode::Replay => Err(CacheError::Miss {
+                namespace: namespace.to_string(),
+                key: key.to_string(),
+            }),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// 记录是否在 `max_age` 以内（无法读取修改时间时视为过期）
+    async fn is_fresh(&self, path: &Path, max_age: Duration) -> bool {
+        fs::metadata(path)
+            .await
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age <= max_age)
+    }
+
+    /// 记录响应正文（写入失败只警告，不影响请求结果）
+    pub async fn put(&self, namespace: &str, key: &str, body: &str) {
+        let path = self.path(namespace, key);
+        if let Some(parent) = path.parent()
+            && let Err(e) = fs::create_dir_all(parent).await
+        {
+            warn!(path = %parent.display(), error = %e, "创建缓存目录失败");
+            return;
+        }
+        if let Err(e) = fs::write(&path, body).await {
+            warn!(path = %path.display(), error = %e, "写入缓存失败");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key() {
+        let key = ResponseCache::key(&["https://api.bgm.tv/v0/subjects/1"]);
+        assert_eq!(key.len(), 64);
+        assert_eq!(
+            key,
+            ResponseCache::key(&["https://api.bgm.tv/v0/subjects/1"])
+        );
+        assert_ne!(
+            ResponseCache::key(&["ab", "c"]),
+            ResponseCache::key(&["a", "bc"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("season-data-cache-{}", std::process::id()));
+        let key = ResponseCache::key(&["request"]);
+
+        let record = ResponseCache::new(&dir, CacheMode::Record);
+        assert!(record.get("bgmtv", &key).await.unwrap().is_none());
+        record.put("bgmtv", &key, r#"{"id":1}"#).await;
+        assert_eq!(
+            record.get("bgmtv", &key).await.unwrap().as_deref(),
+            Some(r#"{"id":1}"#)
+        );
+
+        let replay = ResponseCache::new(&dir, CacheMode::Replay);
+        assert!(replay.get("bgmtv", &key).await.unwrap().is_some());
+        assert!(matches!(
+            replay.get("llm", &key).await,
+            Err(CacheError::Miss { .. })
+        ));
+
+        // 只写模式不返回已缓存的响应，但会覆盖记录
+        let write = ResponseCache::new(&dir, CacheMode::Write);
+        assert!(write.get("bgmtv", &key).await.unwrap().is_none());
+        write.put("bgmtv", &key, r#"{"id":2}"#).await;
+        assert_eq!(
+            replay.get("bgmtv", &key).await.unwrap().as_deref(),
+            Some(r#"{"id":2}"#)
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_max_age() {
+        let dir =
+            std::env::temp_dir().join(format!("season-data-cache-age-{}", std::process::id()));
+        let key = ResponseCache::key(&["request"]);
+
+        let fresh =
+            ResponseCache::new(&dir, CacheMode::Record).with_max_age(Duration::from_secs(3600));
+        fresh.put("bgmtv", &key, r#"{"id":1}"#).await;
+        assert!(fresh.get("bgmtv", &key).await.unwrap().is_some());
+
+        // 过期的记录视为未命中；重放模式不检查
+        let expired = ResponseCache::new(&dir, CacheMode::Record).with_max_age(Duration::ZERO);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(expired.get("bgmtv", &key).await.unwrap().is_none());
+        let replay = ResponseCache::new(&dir, CacheMode::Replay).with_max_age(Duration::ZERO);
+        assert!(replay.get("bgmtv", &key).await.unwrap().is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1429 @@
+mod prompt;
+mod usage;
+
+pub use prompt::PromptTemplate;
+pub use usage::{LlmPricing, UsageStats};
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::services::cache::{CacheError, ResponseCache};
+
+const DEEPSEEK_BASE_URL: &str = "https://api.deepseek.com";
+const DEEPSEEK_MODEL: &str = "deepseek-chat";
+
+/// 单条匹配的输出 token 上限（id + 置信度 + 简短理由）
+const MATCH_MAX_TOKENS: u32 = 128;
+
+/// 批量匹配时每条结果预留的输出 token 数
+const BATCH_TOKENS_PER_ITEM: u32 = 96;
+
+const CACHE_NAMESPACE: &str = "llm";
+
+/// 已保存的匹配判断（`ResponseCache` 中的命名空间）
+const DECISION_NAMESPACE: &str = "decision";
+
+#[derive(Error, Debug)]
+pub enum LlmError {
+    #[error("HTTP request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("API error: {0}")]
+    Api(String),
+    #[error("No response content")]
+    NoContent,
+    #[error("LLM budget exhausted: spent {spent:.4} of {budget:.4}")]
+    BudgetExceeded { spent: f64, budget: f64 },
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
+    #[error("Invalid prompt template: {0}")]
+    Template(String),
+    #[error("Invalid LLM config: {0}")]
+    Config(String),
+}
+
+/// 消息角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    /// 工具调用结果
+    Tool,
+}
+
+/// 聊天消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub role: Role,
+    /// 只有工具调用时服务端可能返回 null，视为空字符串
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub content: String,
+    /// 模型发起的工具调用（assistant）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// 对应的工具调用 ID（tool）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl Message {
+    fn with_role(role: Role, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+            tool_calls: vec![],
+            tool_call_id: None,
+        }
+    }
+
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::with_role(Role::System, content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::with_role(Role::User, content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::with_role(Role::Assistant, content)
+    }
+
+    /// 工具调用结果
+    pub fn tool(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.into()),
+            ..Self::with_role(Role::Tool, content)
+        }
+    }
+}
+
+fn null_as_empty<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// 可供模型调用的工具（目前只有 function 类型）
+#[derive(Debug, Clone, Serialize)]
+pub struct Tool {
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    pub function: FunctionDef,
+}
+
+impl Tool {
+    /// `parameters` 为 JSON Schema
+    pub fn function(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: serde_json::Value,
+    ) -> Self {
+        Self {
+            tool_type: "function".to_string(),
+            function: FunctionDef {
+                name: name.into(),
+                description: description.into(),
+                parameters,
+            },
+        }
+    }
+}
+
+/// 函数定义
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionDef {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+/// 模型发起的工具调用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type", default = "function_type")]
+    pub call_type: String,
+    pub function: FunctionCall,
+}
+
+fn function_type() -> String {
+    "function".to_string()
+}
+
+/// 函数调用（`arguments` 为 JSON 字符串，可能不合法，由调用方校验）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: String,
+}
+
+/// 聊天请求
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+}
+
+/// 输出格式（`{"type": "json_object"}` 为 JSON 模式）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResponseFormat {
+    #[serde(rename = "type")]
+    pub format_type: String,
+}
+
+impl ResponseFormat {
+    pub fn json_object() -> Self {
+        Self {
+            format_type: "json_object".to_string(),
+        }
+    }
+}
+
+impl ChatRequest {
+    pub fn new(messages: Vec<Message>) -> Self {
+        Self {
+            model: "deepseek-chat".to_string(),
+            messages,
+            temperature: Some(0.0), // 确定性输出，适合数据匹配
+            max_tokens: Some(256),  // 限制输出长度节省成本
+            stream: Some(false),
+            response_format: None,
+            tools: None,
+        }
+    }
+
+    /// 允许模型调用工具
+    pub fn with_tools(mut self, tools: Vec<Tool>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// 要求模型输出 JSON 对象（提示中须包含 "JSON" 字样）
+    pub fn with_json_output(mut self) -> Self {
+        self.response_format = Some(ResponseFormat::json_object());
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+}
+
+/// Token 使用统计
+///
+/// 只有 `prompt_tokens` / `completion_tokens` 是 OpenAI 兼容接口的通用字段，
+/// 缓存命中相关字段为 DeepSeek 扩展，其他服务商不会返回
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: u32,
+    #[serde(default)]
+    pub completion_tokens: u32,
+    #[serde(default)]
+    pub total_tokens: u32,
+    #[serde(default)]
+    pub prompt_cache_hit_tokens: Option<u32>,
+    #[serde(default)]
+    pub prompt_cache_miss_tokens: Option<u32>,
+}
+
+/// 响应选项
+#[derive(Debug, Clone, Deserialize)]
+pub struct Choice {
+    #[serde(default)]
+    pub index: u32,
+    pub message: Message,
+    #[serde(default)]
+    pub finish_reason: Option<String>,
+}
+
+/// 聊天响应
+///
+/// 本地服务（llama.cpp / Ollama）可能省略 `id`、`usage` 等字段，因此均带默认值
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatResponse {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub object: String,
+    #[serde(default)]
+    pub created: u64,
+    #[serde(default)]
+    pub model: String,
+    pub choices: Vec<Choice>,
+    #[serde(default)]
+    pub usage: Usage,
+}
+
+impl ChatResponse {
+    /// 获取第一条响应内容
+    pub fn content(&self) -> Option<&str> {
+        self.choices.first().map(|c| c.message.content.as_str())
+    }
+
+    /// 获取第一条响应的消息（包含工具调用）
+    pub fn message(&self) -> Option<&Message> {
+        self.choices.first().map(|c| &c.message)
+    }
+
+    /// 获取缓存命中率（用于监控成本优化效果）
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        match (
+            self.usage.prompt_cache_hit_tokens,
+            self.usage.prompt_cache_miss_tokens,
+        ) {
+            (Some(hit), Some(miss)) if hit + miss > 0 => Some(hit as f64 / (hit + miss) as f64),
+            _ => None,
+        }
+    }
+}
+
+/// LLM 服务配置（任意 OpenAI 兼容接口）
+#[derive(Debug, Clone)]
+pub struct LlmConfig {
+    /// 服务商标识，记录到模型匹配结果中
+    pub provider: String,
+    /// 接口地址，不含 `/chat/completions`
+    pub base_url: String,
+    pub model: String,
+    /// 为 None 时不发送认证头（本地服务）
+    pub api_key: Option<String>,
+    /// 认证头名称，默认 `Authorization`（值为 `Bearer {api_key}`）
+    pub auth_header: Option<String>,
+    /// 是否使用 `response_format` JSON 模式（不支持的服务可关闭）
+    pub json_mode: bool,
+}
+
+impl LlmConfig {
+    pub fn new(
+        provider: impl Into<String>,
+        base_url: impl Into<String>,
+        model: impl Into<String>,
+    ) -> Self {
+        Self {
+            provider: provider.into(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            model: model.into(),
+            api_key: None,
+            auth_header: None,
+            json_mode: true,
+        }
+    }
+
+    /// DeepSeek 官方接口
+    pub fn deepseek(api_key: impl Into<String>) -> Self {
+        Self::new("deepseek", DEEPSEEK_BASE_URL, DEEPSEEK_MODEL).with_api_key(api_key)
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn with_auth_header(mut self, header: impl Into<String>) -> Self {
+        self.auth_header = Some(header.into());
+        self
+    }
+
+    /// 从环境变量读取配置
+    ///
+    /// - `LLM_BASE_URL` / `LLM_MODEL` / `LLM_API_KEY` / `LLM_PROVIDER` / `LLM_AUTH_HEADER`
+    /// - `LLM_JSON_MODE=false` 关闭 JSON 模式
+    /// - 未设置 `LLM_BASE_URL` 时回退到 DeepSeek（`DS_API_KEY` 兼容旧配置）
+    /// - 设置了 `LLM_BASE_URL` 时必须同时设置 `LLM_MODEL`，避免把 DeepSeek 的模型名发给其他服务商
+    ///
+    /// 既没有接口地址也没有密钥时返回 None
+    pub fn from_env() -> Result<Option<Self>, LlmError> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+
+        let api_key = var("LLM_API_KEY").or_else(|| var("DS_API_KEY"));
+        let mut config = match var("LLM_BASE_URL") {
+            Some(base_url) => {
+                let model = var("LLM_MODEL").ok_or_else(|| {
+                    LlmError::Config("设置 LLM_BASE_URL 时必须设置 LLM_MODEL".to_string())
+                })?;
+                let provider =
+                    var("LLM_PROVIDER").unwrap_or_else(|| "openai-compatible".to_string());
+                let mut config = Self::new(provider, base_url, model);
+                config.api_key = api_key;
+                config
+            }
+            None => {
+                let Some(api_key) = api_key else {
+                    return Ok(None);
+                };
+                let mut config = Self::deepseek(api_key);
+                if let Some(model) = var("LLM_MODEL") {
+                    config.model = model;
+                }
+                config
+            }
+        };
+        config.auth_header = var("LLM_AUTH_HEADER");
+        if let Some(json_mode) = var("LLM_JSON_MODE") {
+            config.json_mode = !matches!(json_mode.as_str(), "0" | "false" | "off");
+        }
+        Ok(Some(config))
+    }
+
+    /// 共识模式第二个模型的配置
+    ///
+    /// 读取 `LLM_CONSENSUS_MODEL`（必需）及可选的 `LLM_CONSENSUS_BASE_URL` /
+    /// `LLM_CONSENSUS_API_KEY` / `LLM_CONSENSUS_PROVIDER`，其余沿用当前配置
+    pub fn consensus_from_env(&self) -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+
+        let mut config = self.clone();
+        config.model = var("LLM_CONSENSUS_MODEL")?;
+        if let Some(base_url) = var("LLM_CONSENSUS_BASE_URL") {
+            config.base_url = base_url.trim_end_matches('/').to_string();
+        }
+        if let Some(api_key) = var("LLM_CONSENSUS_API_KEY") {
+            config.api_key = Some(api_key);
+        }
+        if let Some(provider) = var("LLM_CONSENSUS_PROVIDER") {
+            config.provider = provider;
+        }
+        Some(config)
+    }
+}
+
+pub struct LlmClient {
+    client: Client,
+    config: LlmConfig,
+    pricing: LlmPricing,
+    budget: Option<f64>,
+    stats: Arc<Mutex<UsageStats>>,
+    prompt_context: PromptContext,
+    template: PromptTemplate,
+    cache: Option<ResponseCache>,
+    decisions: Option<ResponseCache>,
+    refresh_decisions: bool,
+}
+
+impl LlmClient {
+    pub fn new(config: LlmConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+            pricing: LlmPricing::default(),
+            budget: None,
+            stats: Arc::new(Mutex::new(UsageStats::default())),
+            prompt_context: PromptContext::default(),
+            template: PromptTemplate::default(),
+            cache: None,
+            decisions: None,
+            refresh_decisions: false,
+        }
+    }
+
+    /// 设置响应缓存（记录或重放模型响应，命中时不计入用量）
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// 设置判断缓存：模型、提示版本、MAL 标题与候选集合都相同的判断直接复用
+    pub fn with_decision_cache(mut self, cache: ResponseCache) -> Self {
+        self.decisions = Some(cache);
+        self
+    }
+
+    /// 忽略已保存的判断重新请求（新的判断仍会保存）
+    pub fn with_decision_refresh(mut self, refresh: bool) -> Self {
+        self.refresh_decisions = refresh;
+        self
+    }
+
+    /// 设置提示中包含的条目信息（用于对比不同上下文的匹配准确率）
+    pub fn with_prompt_context(mut self, context: PromptContext) -> Self {
+        self.prompt_context = context;
+        self
+    }
+
+    /// 设置提示模板（模板版本号随每次判断保存）
+    pub fn with_prompt_template(mut self, template: PromptTemplate) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// 设置 token 价格（用于费用估算）
+    pub fn with_pricing(mut self, pricing: LlmPricing) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
+    /// 设置费用预算，估算费用达到预算后不再发送请求
+    pub fn with_budget(mut self, budget: f64) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// 与另一个客户端共用用量统计（预算按两者合计计算）
+    pub fn sharing_usage_with(mut self, other: &LlmClient) -> Self {
+        self.stats = Arc::clone(&other.stats);
+        self
+    }
+
+    pub fn config(&self) -> &LlmConfig {
+        &self.config
+    }
+
+    pub fn pricing(&self) -> &LlmPricing {
+        &self.pricing
+    }
+
+    pub fn prompt_template(&self) -> &PromptTemplate {
+        &self.template
+    }
+
+    /// 本次运行累计的用量
+    pub fn usage_stats(&self) -> UsageStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// 预算是否已用完
+    pub fn budget_exhausted(&self) -> bool {
+        self.check_budget().is_err()
+    }
+
+    fn check_budget(&self) -> Result<(), LlmError> {
+        match self.budget {
+            Some(budget) => {
+                let spent = self.stats.lock().unwrap().cost;
+                if spent >= budget {
+                    Err(LlmError::BudgetExceeded { spent, budget })
+                } else {
+                    Ok(())
+                }
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// 使用配置的模型构建请求
+    fn request(&self, messages: Vec<Message>) -> ChatRequest {
+        ChatRequest::new(messages).with_model(&self.config.model)
+    }
+
+    /// 按配置的提示模板与上下文格式化匹配输入
+    pub fn match_input(&self, mal: &MalEntry<'_>, candidates: &[CandidateEntry<'_>]) -> String {
+        self.template
+            .format_input(mal, candidates, self.prompt_context)
+    }
+
+    /// 判断缓存的键：模型、提示版本与上下文、MAL 标题、排序后的候选 ID
+    ///
+    /// 候选顺序、元数据变化不影响键（同一组候选的判断视为相同）
+    fn decision_key(&self, mal: &MalEntry<'_>, candidates: &[CandidateEntry<'_>]) -> String {
+        let mut ids: Vec<u64> = candidates.iter().map(|c| c.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let ids = ids.iter().map(u64::to_string).collect::<Vec<_>>().join(",");
+        let context = format!("{:?}", self.prompt_context);
+        ResponseCache::key(&[
+            &self.config.model,
+            self.template.version(),
+            &context,
+            mal.title.trim(),
+            mal.title_ja.unwrap_or_default().trim(),
+            &ids,
+        ])
+    }
+
+    /// 读取已保存的判断（未配置判断缓存、强制重新请求或未命中时为 None）
+    pub async fn cached_decision(
+        &self,
+        mal: &MalEntry<'_>,
+        candidates: &[CandidateEntry<'_>],
+    ) -> Option<MatchDecision> {
+        let cache = self
+            .decisions
+            .as_ref()
+            .filter(|_| !self.refresh_decisions)?;
+        let key = self.decision_key(mal, candidates);
+        let body = cache.get(DECISION_NAMESPACE, &key).await.ok()??;
+        match serde_json::from_str(&body) {
+            Ok(decision) => Some(decision),
+            Err(e) => {
+                debug!(key = %key, error = %e, "已保存的判断无法解析，重新请求");
+                None
+            }
+        }
+    }
+
+    /// 保存判断，供之后相同输入复用
+    pub async fn save_decision(
+        &self,
+        mal: &MalEntry<'_>,
+        candidates: &[CandidateEntry<'_>],
+        decision: &MatchDecision,
+    ) {
+        let Some(cache) = &self.decisions else {
+            return;
+        };
+        match serde_json::to_string(decision) {
+            Ok(body) => {
+                let key = self.decision_key(mal, candidates);
+                cache.put(DECISION_NAMESPACE, &key, &body).await;
+            }
+            Err(e) => debug!(error = %e, "序列化判断失败"),
+        }
+    }
+
+    /// 构建带工具的请求（使用配置的模型）
+    ///
+    /// 部分服务不支持工具与 JSON 模式同时使用，因此不开启 JSON 模式，最终输出只依赖提示约束
+    pub fn tool_request(&self, messages: Vec<Message>, tools: Vec<Tool>) -> ChatRequest {
+        self.request(messages).with_tools(tools)
+    }
+
+    /// 构建要求 JSON 输出的请求（配置关闭 JSON 模式时只依赖提示）
+    fn json_request(&self, messages: Vec<Message>) -> ChatRequest {
+        let request = self.request(messages);
+        if self.config.json_mode {
+            request.with_json_output()
+        } else {
+            request
+        }
+    }
+
+    /// 发送聊天请求
+    pub async fn chat(&self, request: &ChatRequest) -> Result<ChatResponse, LlmError> {
+        let url = format!("{}/chat/completions", self.config.base_url);
+
+        let cache_key = ResponseCache::key(&[&url, &serde_json::to_string(request)?]);
+        if let Some(cache) = &self.cache
+            && let Some(body) = cache.get(CACHE_NAMESPACE, &cache_key).await?
+        {
+            return Ok(serde_json::from_str(&body)?);
+        }
+
+        self.check_budget()?;
+
+        let mut req = self
+            .client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .json(request);
+
+        if let Some(api_key) = &self.config.api_key {
+            req = match &self.config.auth_header {
+                Some(header) => req.header(header.as_str(), api_key),
+                None => req.header("Authorization", format!("Bearer {}", api_key)),
+            };
+        }
+
+        let response = req.send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(LlmError::Api(format!("{}: {}", status, text)));
+        }
+
+        let body = response.text().await?;
+        let result: ChatResponse = serde_json::from_str(&body)?;
+        if let Some(cache) = &self.cache {
+            cache.put(CACHE_NAMESPACE, &cache_key, &body).await;
+        }
+        self.stats
+            .lock()
+            .unwrap()
+            .record(&result.usage, &self.pricing);
+        Ok(result)
+    }
+
+    /// 简单对话（单轮）
+    pub async fn ask(&self, prompt: &str) -> Result<String, LlmError> {
+        let request = self.request(vec![Message::user(prompt)]);
+        let response = self.chat(&request).await?;
+        response
+            .content()
+            .map(|s| s.to_string())
+            .ok_or(LlmError::NoContent)
+    }
+
+    /// 带系统提示的对话
+    pub async fn ask_with_system(
+        &self,
+        system: &str,
+        prompt: &str,
+    ) -> Result<ChatResponse, LlmError> {
+        let request = self.request(vec![Message::system(system), Message::user(prompt)]);
+        self.chat(&request).await
+    }
+
+    /// 当前配置对应的判定者信息
+    pub fn judge(&self) -> LlmJudge {
+        LlmJudge {
+            provider: self.config.provider.clone(),
+            model: self.config.model.clone(),
+            prompt_version: Some(self.template.version().to_string()),
+        }
+    }
+
+    /// 以服务端返回的模型名为准（部分服务会把别名解析为具体版本）
+    fn judge_for(&self, response: &ChatResponse) -> LlmJudge {
+        let mut judge = self.judge();
+        if !response.model.is_empty() {
+            judge.model = response.model.clone();
+        }
+        judge
+    }
+
+    /// 动漫匹配验证
+    ///
+    /// 返回匹配的 Bangumi ID（无匹配为 None）、置信度、理由及做出判断的服务商/模型
+    pub async fn match_anime(
+        &self,
+        mal: &MalEntry<'_>,
+        candidates: &[CandidateEntry<'_>],
+    ) -> Result<MatchDecision, LlmError> {
+        if candidates.is_empty() {
+            return Ok(MatchDecision::new(MatchResult::default(), self.judge()));
+        }
+
+        let input = self.match_input(mal, candidates);
+
+        let request = self
+            .json_request(vec![
+                Message::system(self.template.system()),
+                Message::user(&input),
+            ])
+            .with_max_tokens(MATCH_MAX_TOKENS);
+
+        let response = self.chat(&request).await?;
+        let content = response.content().ok_or(LlmError::NoContent)?;
+
+        debug!(
+            input = %input,
+            output = %content,
+            cache_hit = ?response.cache_hit_ratio(),
+            "anime match"
+        );
+
+        self.parse_decision(&response)
+    }
+
+    /// 解析匹配判断 `{"id": 123, "conf": "high", "reason": "..."}` 或 `{"id": null, ...}`
+    ///
+    /// 未开启 JSON 模式时 LLM 有时会返回 markdown 代码块包裹的 JSON
+    pub fn parse_decision(&self, response: &ChatResponse) -> Result<MatchDecision, LlmError> {
+        let content = response.content().ok_or(LlmError::NoContent)?;
+        let result: MatchResult = serde_json::from_str(extract_json(content))
+            .map_err(|e| LlmError::Api(format!("Invalid JSON: {} - {}", content, e)))?;
+
+        Ok(MatchDecision::new(result, self.judge_for(response)))
+    }
+
+    /// 批量动漫匹配验证（一次请求匹配多个条目）
+    ///
+    /// 返回与 `items` 一一对应的结果；某条结果缺失、重复或 ID 不在该条目候选中时为 None，
+    /// 由调用方回退到 [`Self::match_anime`]。整个响应无法解析（或被截断）时返回错误
+    pub async fn match_anime_batch(
+        &self,
+        items: &[BatchItem<'_>],
+    ) -> Result<Vec<Option<MatchDecision>>, LlmError> {
+        if items.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut input = String::new();
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                input.push('\n');
+            }
+            input.push_str(&format!("#{}\n", i + 1));
+            input.push_str(&self.match_input(&item.mal, item.candidates));
+        }
+
+        let request = self
+            .json_request(vec![
+                Message::system(self.template.batch_system()),
+                Message::user(&input),
+            ])
+            .with_max_tokens(16 + BATCH_TOKENS_PER_ITEM * items.len() as u32);
+
+        let response = self.chat(&request).await?;
+        let content = response.content().ok_or(LlmError::NoContent)?;
+
+        debug!(
+            batch_size = items.len(),
+            output = %content,
+            cache_hit = ?response.cache_hit_ratio(),
+            "anime batch match"
+        );
+
+        let truncated = response
+            .choices
+            .first()
+            .and_then(|c| c.finish_reason.as_deref())
+            == Some("length");
+        if truncated {
+            return Err(LlmError::Api(format!(
+                "Truncated batch response: {}",
+                content
+            )));
+        }
+
+        let candidate_ids: Vec<Vec<u64>> = items
+            .iter()
+            .map(|item| item.candidates.iter().map(|c| c.id).collect())
+            .collect();
+        let results = parse_batch_response(content, &candidate_ids)?;
+
+        let judge = self.judge_for(&response);
+        Ok(results
+            .into_iter()
+            .map(|result| result.map(|result| MatchDecision::new(result, judge.clone())))
+            .collect())
+    }
+}
+
+/// 提示中包含的条目信息
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PromptContext {
+    /// 只有标题
+    Titles,
+    /// 标题 + 类型、开播日期、集数、制作公司（默认）
+    #[default]
+    Full,
+}
+
+impl std::str::FromStr for PromptContext {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "titles" => Ok(PromptContext::Titles),
+            "full" => Ok(PromptContext::Full),
+            _ => Err(format!("unknown prompt context: {}", s)),
+        }
+    }
+}
+
+/// 待匹配的 MAL 条目
+#[derive(Debug, Clone, Default)]
+pub struct MalEntry<'a> {
+    pub title: &'a str,
+    pub title_ja: Option<&'a str>,
+    /// MAL media_type 原始值（tv/movie/ova/...）
+    pub media_type: Option<&'a str>,
+    pub start_date: Option<&'a str>,
+    pub num_episodes: Option<u32>,
+    pub studios: Vec<&'a str>,
+}
+
+/// Bangumi 候选条目
+#[derive(Debug, Clone, Default)]
+pub struct CandidateEntry<'a> {
+    pub id: u64,
+    pub name: &'a str,
+    pub name_cn: Option<&'a str>,
+    pub date: Option<&'a str>,
+    pub eps: Option<u32>,
+    /// Bangumi platform（TV/OVA/剧场版/WEB/...）
+    pub platform: Option<&'a str>,
+    /// 信息框 `动画制作`
+    pub studios: Vec<String>,
+    /// 与 MAL 制作公司是否一致（任一方缺失时为 None）
+    pub studio_match: Option<bool>,
+}
+
+/// 批量匹配中的单个条目
+#[derive(Debug, Clone)]
+pub struct BatchItem<'a> {
+    pub mal: MalEntry<'a>,
+    pub candidates: &'a [CandidateEntry<'a>],
+}
+
+/// 批量匹配的单条结果
+#[derive(Debug, Deserialize)]
+struct BatchMatchResult {
+    i: usize,
+    #[serde(flatten)]
+    result: MatchResult,
+}
+
+/// 批量匹配响应，JSON 模式下为 `{"r": [...]}`，兼容直接返回数组
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum BatchMatchResponse {
+    Object { r: Vec<BatchMatchResult> },
+    Array(Vec<BatchMatchResult>),
+}
+
+/// 解析并校验批量匹配响应
+///
+/// `candidate_ids[k]` 为第 k+1 条的候选 ID。返回值为 None 表示该条结果无效
+/// （缺失、重复、ID 不属于该条目的候选）
+fn parse_batch_response(
+    content: &str,
+    candidate_ids: &[Vec<u64>],
+) -> Result<Vec<Option<MatchResult>>, LlmError> {
+    let response: BatchMatchResponse = serde_json::from_str(extract_json(content))
+        .map_err(|e| LlmError::Api(format!("Invalid JSON: {} - {}", content, e)))?;
+    let results = match response {
+        BatchMatchResponse::Object { r } => r,
+        BatchMatchResponse::Array(r) => r,
+    };
+
+    let mut parsed = vec![None; candidate_ids.len()];
+    let mut seen = vec![false; candidate_ids.len()];
+    for BatchMatchResult { i, result } in results {
+        let Some(index) = i.checked_sub(1).filter(|&k| k < candidate_ids.len()) else {
+            continue;
+        };
+        if seen[index] {
+            // 同一条目返回多次，无法判断以哪次为准
+            parsed[index] = None;
+            continue;
+        }
+        seen[index] = true;
+
+        let valid = match result.id {
+            Some(id) => candidate_ids[index].contains(&id),
+            None => true,
+        };
+        if valid {
+            parsed[index] = Some(result);
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// 做出模型匹配判断的服务商与模型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmJudge {
+    pub provider: String,
+    pub model: String,
+    /// 提示模板版本（旧数据没有该字段）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<String>,
+}
+
+/// 模型给出的置信度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    High,
+    #[serde(alias = "mid")]
+    Medium,
+    Low,
+}
+
+/// 模型匹配判断（保存到条目中供人工复核）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchDecision {
+    /// 模型选中的 Bangumi ID，无匹配为 None
+    #[serde(rename = "bgm_id", default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<Confidence>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// 工具调用模式下模型自行搜索过的关键词
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub searches: Vec<String>,
+    #[serde(flatten)]
+    pub judge: LlmJudge,
+}
+
+impl MatchDecision {
+    fn new(result: MatchResult, judge: LlmJudge) -> Self {
+        Self {
+            id: result.id,
+            confidence: result.conf,
+            reason: result.reason.filter(|r| !r.trim().is_empty()),
+            searches: vec![],
+            judge,
+        }
+    }
+
+    /// 是否为低置信度（需人工复核）
+    pub fn is_low_confidence(&self) -> bool {
+        self.confidence == Some(Confidence::Low)
+    }
+}
+
+/// 匹配结果
+#[derive(Debug, Clone, Default, Deserialize)]
+struct MatchResult {
+    id: Option<u64>,
+    #[serde(default, alias = "confidence")]
+    conf: Option<Confidence>,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// 从 LLM 响应中提取 JSON 内容
+///
+/// 处理以下情况：
+/// - 纯 JSON: `{"id": 123}`
+/// - markdown 代码块: `\`\`\`json\n{"id": 123}\n\`\`\``
+/// - 带语言标记或不带: `\`\`\`\n{"id": 123}\n\`\`\``
+fn extract_json(content: &str) -> &str {
+    let trimmed = content.trim();
+
+    // 检查是否被 markdown 代码块包裹
+    if trimmed.starts_with("```") {
+        // 找到第一个换行符后的内容
+        if let Some(start) = trimmed.find('\n') {
+            let after_start = &trimmed[start + 1..];
+            // 找到结束的 ```
+            if let Some(end) = after_start.rfind("```") {
+                return after_start[..end].trim();
+            }
+        }
+    }
+
+    trimmed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::cache::CacheMode;
+
+    #[test]
+    fn test_message_creation() {
+        let system = Message::system("你是助手");
+        assert_eq!(system.role, Role::System);
+        assert_eq!(system.content, "你是助手");
+
+        let user = Message::user("你好");
+        assert_eq!(user.role, Role::User);
+    }
+
+    #[test]
+    fn test_chat_request_defaults() {
+        let request = ChatRequest::new(vec![Message::user("test")]);
+
+        assert_eq!(request.model, "deepseek-chat");
+        assert_eq!(request.temperature, Some(0.0));
+        assert_eq!(request.max_tokens, Some(256));
+        assert_eq!(request.stream, Some(false));
+    }
+
+    #[test]
+    fn test_chat_request_serialization() {
+        let request = ChatRequest::new(vec![
+            Message::system("你是助手"),
+            Message::user("你好"),
+        ]);
+
+        let json = serde_json::to_string(&request).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed["model"], "deepseek-chat");
+        assert_eq!(parsed["temperature"], 0.0);
+        assert_eq!(parsed["max_tokens"], 256);
+        assert_eq!(parsed["messages"][0]["role"], "system");
+        assert_eq!(parsed["messages"][1]["role"], "user");
+    }
+
+    #[test]
+    fn test_deserialize_response() {
+        let json = r#"{
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "created": 1700000000,
+            "model": "deepseek-chat",
+            "choices": [
+                {
+                    "index": 0,
+                    "message": {
+                        "role": "assistant",
+                        "content": "你好！"
+                    },
+                    "finish_reason": "stop"
+                }
+            ],
+            "usage": {
+                "prompt_tokens": 10,
+                "completion_tokens": 5,
+                "total_tokens": 15,
+                "prompt_cache_hit_tokens": 8,
+                "prompt_cache_miss_tokens": 2
+            }
+        }"#;
+
+        let response: ChatResponse = serde_json::from_str(json).unwrap();
+
+        assert_eq!(response.id, "chatcmpl-123");
+        assert_eq!(response.content(), Some("你好！"));
+        assert_eq!(response.usage.prompt_tokens, 10);
+        assert_eq!(response.usage.prompt_cache_hit_tokens, Some(8));
+
+        // 缓存命中率 = 8 / (8 + 2) = 0.8
+        let hit_ratio = response.cache_hit_ratio().unwrap();
+        assert!((hit_ratio - 0.8).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_deserialize_response_without_cache_info() {
+        let json = r#"{
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "created": 1700000000,
+            "model": "deepseek-chat",
+            "choices": [
+                {
+                    "index": 0,
+                    "message": {
+                        "role": "assistant",
+                        "content": "你好！"
+                    }
+                }
+            ],
+            "usage": {
+                "prompt_tokens": 10,
+                "completion_tokens": 5,
+                "total_tokens": 15
+            }
+        }"#;
+
+        let response: ChatResponse = serde_json::from_str(json).unwrap();
+        assert!(response.cache_hit_ratio().is_none());
+    }
+
+    #[test]
+    fn test_match_result_deserialize() {
+        let json = r#"{"id":400602}"#;
+        let result: MatchResult = serde_json::from_str(json).unwrap();
+        assert_eq!(result.id, Some(400602));
+
+        let json_null = r#"{"id":null}"#;
+        let result_null: MatchResult = serde_json::from_str(json_null).unwrap();
+        assert_eq!(result_null.id, None);
+    }
+
+    #[test]
+    fn test_deserialize_minimal_local_response() {
+        // llama.cpp / Ollama 等本地服务可能省略 id、usage
+        let json = r#"{
+            "choices": [
+                {"message": {"role": "assistant", "content": "{\"id\":null}"}}
+            ]
+        }"#;
+
+        let response: ChatResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.content(), Some(r#"{"id":null}"#));
+        assert_eq!(response.usage.prompt_tokens, 0);
+        assert!(response.cache_hit_ratio().is_none());
+    }
+
+    #[test]
+    fn test_llm_config() {
+        let config = LlmConfig::deepseek("sk-test");
+        assert_eq!(config.provider, "deepseek");
+        assert_eq!(config.base_url, "https://api.deepseek.com");
+        assert_eq!(config.model, "deepseek-chat");
+        assert_eq!(config.api_key.as_deref(), Some("sk-test"));
+
+        // 去掉末尾斜杠，避免拼出 `//chat/completions`
+        let config = LlmConfig::new("ollama", "http://localhost:11434/v1/", "qwen2.5:7b");
+        assert_eq!(config.base_url, "http://localhost:11434/v1");
+        assert!(config.api_key.is_none());
+
+        let client = LlmClient::new(config);
+        assert_eq!(
+            client.judge(),
+            LlmJudge {
+                provider: "ollama".to_string(),
+                model: "qwen2.5:7b".to_string(),
+                prompt_version: Some("builtin-2".to_string()),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decision_cache() {
+        let dir =
+            std::env::temp_dir().join(format!("season-data-decisions-{}", std::process::id()));
+        let cache = ResponseCache::new(&dir, CacheMode::Record);
+        let client =
+            LlmClient::new(LlmConfig::deepseek("sk-test")).with_decision_cache(cache.clone());
+
+        let mal = MalEntry {
+            title: "Test",
+            title_ja: Some("テスト"),
+            ..Default::default()
+        };
+        let candidates = [
+            CandidateEntry {
+                id: 2,
+                name: "b",
+                ..Default::default()
+            },
+            CandidateEntry {
+                id: 1,
+                name: "a",
+                ..Default::default()
+            },
+        ];
+        assert!(client.cached_decision(&mal, &candidates).await.is_none());
+
+        let decision = MatchDecision::new(
+            serde_json::from_str(r#"{"id":null,"conf":"high","reason":"无对应条目"}"#).unwrap(),
+            client.judge(),
+        );
+        client.save_decision(&mal, &candidates, &decision).await;
+
+        // 候选顺序不影响复用
+        let reversed = [candidates[1].clone(), candidates[0].clone()];
+        let cached = client.cached_decision(&mal, &reversed).await.unwrap();
+        assert_eq!(cached.reason.as_deref(), Some("无对应条目"));
+        assert_eq!(cached.judge, client.judge());
+
+        // 候选集合、模型变化或强制重新请求时不复用
+        assert!(
+            client
+                .cached_decision(&mal, &candidates[..1])
+                .await
+                .is_none()
+        );
+        let mut config = LlmConfig::deepseek("sk-test");
+        config.model = "deepseek-reasoner".to_string();
+        let other = LlmClient::new(config).with_decision_cache(cache);
+        assert!(other.cached_decision(&mal, &candidates).await.is_none());
+        let client = client.with_decision_refresh(true);
+        assert!(client.cached_decision(&mal, &candidates).await.is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_budget_exhausted() {
+        let client = LlmClient::new(LlmConfig::deepseek("sk-test"));
+        assert!(!client.budget_exhausted());
+
+        let client = client
+            .with_pricing(LlmPricing {
+                prompt: 1.0,
+                prompt_cache_hit: 1.0,
+                completion: 1.0,
+            })
+            .with_budget(0.001);
+        assert!(!client.budget_exhausted());
+
+        client.stats.lock().unwrap().record(
+            &Usage {
+                prompt_tokens: 900,
+                completion_tokens: 100,
+                ..Default::default()
+            },
+            &client.pricing,
+        );
+        assert!(client.budget_exhausted());
+        assert_eq!(client.usage_stats().requests, 1);
+
+        // 共用用量统计的客户端一起受预算限制
+        let second = LlmClient::new(LlmConfig::deepseek("sk-test")).sharing_usage_with(&client);
+        assert_eq!(second.usage_stats().requests, 1);
+    }
+
+    #[test]
+    fn test_chat_request_json_output() {
+        let request = ChatRequest::new(vec![Message::user("test")]);
+        let parsed = serde_json::to_value(&request).unwrap();
+        assert!(parsed.get("response_format").is_none());
+
+        let parsed = serde_json::to_value(request.with_json_output()).unwrap();
+        assert_eq!(parsed["response_format"]["type"], "json_object");
+    }
+
+    #[test]
+    fn test_tool_calls_round_trip() {
+        let request = ChatRequest::new(vec![Message::user("test")]);
+        let parsed = serde_json::to_value(&request).unwrap();
+        assert!(parsed.get("tools").is_none());
+        assert!(parsed["messages"][0].get("tool_calls").is_none());
+
+        let tool = Tool::function(
+            "get_subject",
+            "查看条目",
+            serde_json::json!({"type": "object"}),
+        );
+        let parsed = serde_json::to_value(request.with_tools(vec![tool])).unwrap();
+        assert_eq!(parsed["tools"][0]["type"], "function");
+        assert_eq!(parsed["tools"][0]["function"]["name"], "get_subject");
+
+        // 只有工具调用时 content 为 null
+        let json = r#"{
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_0",
+                        "type": "function",
+                        "function": {"name": "get_subject", "arguments": "{\"id\":1}"}
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }]
+        }"#;
+        let response: ChatResponse = serde_json::from_str(json).unwrap();
+        let message = response.message().unwrap();
+        assert_eq!(message.content, "");
+        assert_eq!(message.tool_calls[0].function.arguments, r#"{"id":1}"#);
+
+        // 原样发回时保留工具调用，工具结果带调用 ID
+        let parsed = serde_json::to_value(message).unwrap();
+        assert_eq!(parsed["tool_calls"][0]["id"], "call_0");
+        let parsed = serde_json::to_value(Message::tool("call_0", "ok")).unwrap();
+        assert_eq!(parsed["role"], "tool");
+        assert_eq!(parsed["tool_call_id"], "call_0");
+    }
+
+    #[test]
+    fn test_match_result_with_confidence() {
+        let json = r#"{"id":400602,"conf":"low","reason":"季数不确定"}"#;
+        let result: MatchResult = serde_json::from_str(json).unwrap();
+        let decision = MatchDecision::new(
+            result,
+            LlmJudge {
+                provider: "deepseek".to_string(),
+                model: "deepseek-chat".to_string(),
+                prompt_version: None,
+            },
+        );
+        assert_eq!(decision.id, Some(400602));
+        assert!(decision.is_low_confidence());
+        assert_eq!(decision.reason.as_deref(), Some("季数不确定"));
+
+        // 保存格式：判断与服务商/模型平铺在同一对象
+        let value = serde_json::to_value(&decision).unwrap();
+        assert_eq!(value["bgm_id"], 400602);
+        assert_eq!(value["confidence"], "low");
+        assert_eq!(value["provider"], "deepseek");
+
+        // 旧数据只有服务商/模型
+        let old: MatchDecision =
+            serde_json::from_str(r#"{"provider":"deepseek","model":"deepseek-chat"}"#).unwrap();
+        assert_eq!(old.id, None);
+        assert!(!old.is_low_confidence());
+
+        // 兼容完整字段名，空理由不保存
+        let json = r#"{"id":null,"confidence":"high","reason":" "}"#;
+        let result: MatchResult = serde_json::from_str(json).unwrap();
+        assert_eq!(result.conf, Some(Confidence::High));
+        let decision = MatchDecision::new(result, old.judge);
+        assert!(decision.reason.is_none());
+    }
+
+    #[test]
+    fn test_prompt_context_from_str() {
+        assert_eq!("titles".parse(), Ok(PromptContext::Titles));
+        assert_eq!("full".parse(), Ok(PromptContext::Full));
+        assert!("other".parse::<PromptContext>().is_err());
+    }
+
+    /// 外层 None 为无效结果，内层为模型选中的 ID
+    fn batch_ids(results: &[Option<MatchResult>]) -> Vec<Option<Option<u64>>> {
+        results.iter().map(|r| r.as_ref().map(|r| r.id)).collect()
+    }
+
+    #[test]
+    fn test_parse_batch_response() {
+        let candidate_ids = vec![vec![100, 101], vec![200], vec![300, 301]];
+
+        let content = r#"{"r":[
+            {"i":1,"id":101,"conf":"high","reason":"标题一致"},
+            {"i":2,"id":null,"conf":"low"},
+            {"i":3,"id":300,"conf":"mid"}
+        ]}"#;
+        let results = parse_batch_response(content, &candidate_ids).unwrap();
+        assert_eq!(
+            batch_ids(&results),
+            vec![Some(Some(101)), Some(None), Some(Some(300))]
+        );
+        let first = results[0].as_ref().unwrap();
+        assert_eq!(first.conf, Some(Confidence::High));
+        assert_eq!(first.reason.as_deref(), Some("标题一致"));
+        assert_eq!(results[2].as_ref().unwrap().conf, Some(Confidence::Medium));
+
+        // 兼容直接返回数组，以及 markdown 包裹
+        let content = "```json\n[{\"i\":2,\"id\":200}]\n```";
+        let results = parse_batch_response(content, &candidate_ids).unwrap();
+        assert_eq!(batch_ids(&results), vec![None, Some(Some(200)), None]);
+    }
+
+    #[test]
+    fn test_parse_batch_response_rejects_invalid_entries() {
+        let candidate_ids = vec![vec![100], vec![200], vec![300]];
+
+        // 1: ID 属于其他条目的候选；2: 重复返回；3: 正常；9: 序号越界
+        let content = r#"[
+            {"i":1,"id":200},
+            {"i":2,"id":200},
+            {"i":2,"id":null},
+            {"i":3,"id":300},
+            {"i":9,"id":100}
+        ]"#;
+        let results = parse_batch_response(content, &candidate_ids).unwrap();
+        assert_eq!(batch_ids(&results), vec![None, None, Some(Some(300))]);
+
+        // 整体格式错误
+        assert!(parse_batch_response(r#"{"id":100}"#, &candidate_ids).is_err());
+        assert!(parse_batch_response("无法判断", &candidate_ids).is_err());
+    }
+
+    #[test]
+    fn test_extract_json_plain() {
+        assert_eq!(extract_json(r#"{"id": 123}"#), r#"{"id": 123}"#);
+        assert_eq!(extract_json(r#"  {"id": null}  "#), r#"{"id": null}"#);
+    }
+
+    #[test]
+    fn test_extract_json_markdown_with_lang() {
+        let input = "```json\n{\"id\": 443446}\n```";
+        assert_eq!(extract_json(input), r#"{"id": 443446}"#);
+    }
+
+    #[test]
+    fn test_extract_json_markdown_without_lang() {
+        let input = "```\n{\"id\": 123}\n```";
+        assert_eq!(extract_json(input), r#"{"id": 123}"#);
+    }
+
+    #[test]
+    fn test_extract_json_markdown_multiline() {
+        let input = "```json\n{\n  \"id\": 443446\n}\n```";
+        let extracted = extract_json(input);
+        let result: MatchResult = serde_json::from_str(extracted).unwrap();
+        assert_eq!(result.id, Some(443446));
+    }
+}
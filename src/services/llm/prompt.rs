@@ -0,0 +1,457 @@
+use super::{CandidateEntry, LlmError, MalEntry, PromptContext};
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// 内置提示模板
+const BUILTIN_TEMPLATE: &str = include_str!("prompt.txt");
+
+static BUILTIN: LazyLock<PromptTemplate> =
+    LazyLock::new(|| PromptTemplate::parse(BUILTIN_TEMPLATE).expect("内置提示模板无效"));
+
+/// MAL 行可用的占位符
+const MAL_FIELDS: &[&str] = &[
+    "title",
+    "title_ja",
+    "media_type",
+    "start_date",
+    "episodes",
+    "studios",
+    "meta",
+];
+
+/// 候选行可用的占位符
+const CANDIDATE_FIELDS: &[&str] = &[
+    "id",
+    "name",
+    "name_cn",
+    "platform",
+    "date",
+    "eps",
+    "studios",
+    "studio_match",
+    "meta",
+];
+
+/// 行模板的组成部分
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Field(String),
+    /// `[...]`：其中任一占位符为空时整段省略
+    Optional(Vec<Segment>),
+}
+
+/// 带版本号的匹配提示模板
+///
+/// 系统提示原样发送；MAL 行与候选行为带占位符的单行模板，用户消息按
+///
+/// ```text
+/// {mal}
+/// BGM:
+/// {candidate}
+/// ```
+///
+/// 拼接。版本号随每次模型判断保存，用于只重跑旧版本提示做出的判断
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    version: String,
+    system: String,
+    batch_system: String,
+    mal: Vec<Segment>,
+    candidate: Vec<Segment>,
+}
+
+impl Default for PromptTemplate {
+    fn default() -> Self {
+        Self::builtin().clone()
+    }
+}
+
+impl PromptTemplate {
+    /// 内置模板（`prompt.txt`）
+    pub fn builtin() -> &'static PromptTemplate {
+        &BUILTIN
+    }
+
+    /// 从文件加载模板
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, LlmError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| LlmError::Template(format!("{}: {}", path.display(), e)))?;
+        Self::parse(&text).map_err(|e| match e {
+            LlmError::Template(msg) => LlmError::Template(format!("{}: {}", path.display(), msg)),
+            e => e,
+        })
+    }
+
+    /// 解析模板
+    ///
+    /// ```text
+    /// # 注释
+    /// version = v2
+    ///
+    /// --- system
+    /// ...
+    /// --- batch_system
+    /// ...
+    /// --- mal
+    /// MAL:{title}[|{title_ja}][({meta})]
+    /// --- candidate
+    /// {id}:{name}[|{name_cn}][({meta})]
+    /// ```
+    ///
+    /// 占位符在加载时校验，未知占位符、括号不匹配、缺少分节均为错误
+    pub fn parse(text: &str) -> Result<Self, LlmError> {
+        let invalid = |msg: String| LlmError::Template(msg);
+
+        let mut version = None;
+        let mut sections: Vec<(String, String)> = Vec::new();
+        for line in text.lines() {
+            if let Some(name) = line.strip_prefix("---") {
+                let name = name.trim();
+                if !["system", "batch_system", "mal", "candidate"].contains(&name) {
+                    return Err(invalid(format!("未知分节: {}", name)));
+                }
+                if sections.iter().any(|(n, _)| n == name) {
+                    return Err(invalid(format!("重复的分节: {}", name)));
+                }
+                sections.push((name.to_string(), String::new()));
+                continue;
+            }
+
+            match sections.last_mut() {
+                Some((_, body)) => {
+                    body.push_str(line);
+                    body.push('\n');
+                }
+                None => {
+                    let line = line.trim();
+                    if line.is_empty() || line.starts_with('#') {
+                        continue;
+                    }
+                    match line.split_once('=') {
+                        Some((key, value)) if key.trim() == "version" => {
+                            version = Some(value.trim().to_string());
+                        }
+                        _ => return Err(invalid(format!("无效的头部: {}", line))),
+                    }
+                }
+            }
+        }
+
+        let version = version
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| invalid("缺少 version".to_string()))?;
+        let section = |name: &str| {
+            sections
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, body)| body.trim().to_string())
+                .filter(|body| !body.is_empty())
+                .ok_or_else(|| invalid(format!("缺少分节: {}", name)))
+        };
+        let line = |name: &str, fields: &[&str]| {
+            let body = section(name)?;
+            if body.contains('\n') {
+                return Err(invalid(format!("{} 模板只能有一行", name)));
+            }
+            parse_line(&body, fields).map_err(|e| invalid(format!("{}: {}", name, e)))
+        };
+
+        Ok(Self {
+            system: section("system")?,
+            batch_system: section("batch_system")?,
+            mal: line("mal", MAL_FIELDS)?,
+            candidate: line("candidate", CANDIDATE_FIELDS)?,
+            version,
+        })
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// 单条匹配的系统提示
+    pub fn system(&self) -> &str {
+        &self.system
+    }
+
+    /// 批量匹配的系统提示
+    pub fn batch_system(&self) -> &str {
+        &self.batch_system
+    }
+
+    /// MAL 行
+    ///
+    /// `{meta}` 为 `media_type,start_date,Nep,studios`（缺失字段省略）；
+    /// `PromptContext::Titles` 时标题以外的占位符均为空
+    pub fn mal_line(&self, mal: &MalEntry<'_>, context: PromptContext) -> String {
+        let full = context == PromptContext::Full;
+        let episodes = mal.num_episodes.filter(|&n| n > 0).map(|n| n.to_string());
+        render(&self.mal, &|field| match field {
+            "title" => mal.title.to_string(),
+            "title_ja" => mal.title_ja.unwrap_or_default().to_string(),
+            _ if !full => String::new(),
+            "media_type" => mal.media_type.unwrap_or_default().to_string(),
+            "start_date" => mal.start_date.unwrap_or_default().to_string(),
+            "episodes" => episodes.clone().unwrap_or_default(),
+            "studios" => mal.studios.join(","),
+            "meta" => join_meta(
+                [mal.media_type, mal.start_date]
+                    .into_iter()
+                    .flatten()
+                    .map(str::to_string)
+                    .chain(episodes.as_ref().map(|n| format!("{}ep", n)))
+                    .chain(mal.studios.iter().map(|s| s.to_string())),
+            ),
+            _ => String::new(),
+        })
+    }
+
+    /// 候选行
+    ///
+    /// `{meta}` 为 `platform,date,Nep,studios,制作一致|制作不同`（缺失字段省略）；
+    /// `PromptContext::Titles` 时 ID 与标题以外的占位符均为空
+    pub fn candidate_line(&self, candidate: &CandidateEntry<'_>, context: PromptContext) -> String {
+        let full = context == PromptContext::Full;
+        let eps = candidate.eps.filter(|&n| n > 0).map(|n| n.to_string());
+        let studio_match = candidate.studio_match.map(|agree| {
+            if agree {
+                "制作一致"
+            } else {
+                "制作不同"
+            }
+        });
+        render(&self.candidate, &|field| match field {
+            "id" => candidate.id.to_string(),
+            "name" => candidate.name.to_string(),
+            "name_cn" => candidate.name_cn.unwrap_or_default().to_string(),
+            _ if !full => String::new(),
+            "platform" => candidate.platform.unwrap_or_default().to_string(),
+            "date" => candidate.date.unwrap_or_default().to_string(),
+            "eps" => eps.clone().unwrap_or_default(),
+            "studios" => candidate.studios.join(","),
+            "studio_match" => studio_match.unwrap_or_default().to_string(),
+            "meta" => join_meta(
+                [candidate.platform, candidate.date]
+                    .into_iter()
+                    .flatten()
+                    .map(str::to_string)
+                    .chain(eps.as_ref().map(|n| format!("{}ep", n)))
+                    .chain(candidate.studios.iter().cloned())
+                    .chain(studio_match.map(str::to_string)),
+            ),
+            _ => String::new(),
+        })
+    }
+
+    /// 单个条目的用户输入
+    pub fn format_input(
+        &self,
+        mal: &MalEntry<'_>,
+        candidates: &[CandidateEntry<'_>],
+        context: PromptContext,
+    ) -> String {
+        let mut input = self.mal_line(mal, context);
+        input.push_str("\nBGM:");
+        for c in candidates {
+            input.push('\n');
+            input.push_str(&self.candidate_line(c, context));
+        }
+        input
+    }
+}
+
+/// 逗号连接非空的元数据
+fn join_meta(values: impl Iterator<Item = String>) -> String {
+    values
+        .filter(|v| !v.is_empty())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// 解析单行模板，`fields` 为允许的占位符
+fn parse_line(line: &str, fields: &[&str]) -> Result<Vec<Segment>, String> {
+    let mut root = Vec::new();
+    let mut optional: Option<Vec<Segment>> = None;
+    let mut text = String::new();
+    let mut chars = line.chars();
+
+    fn flush(text: &mut String, root: &mut Vec<Segment>, optional: &mut Option<Vec<Segment>>) {
+        if !text.is_empty() {
+            let segment = Segment::Text(std::mem::take(text));
+            optional.as_mut().unwrap_or(root).push(segment);
+        }
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => {
+                if optional.is_some() {
+                    return Err("可选段不能嵌套".to_string());
+                }
+                flush(&mut text, &mut root, &mut optional);
+                optional = Some(Vec::new());
+            }
+            ']' => {
+                flush(&mut text, &mut root, &mut optional);
+                let segments = optional.take().ok_or("多余的 ]")?;
+                root.push(Segment::Optional(segments));
+            }
+            '{' => {
+                flush(&mut text, &mut root, &mut optional);
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err("缺少 }".to_string()),
+                    }
+                }
+                if !fields.contains(&name.as_str()) {
+                    return Err(format!("未知占位符 {{{}}}", name));
+                }
+                optional
+                    .as_mut()
+                    .unwrap_or(&mut root)
+                    .push(Segment::Field(name));
+            }
+            '}' => return Err("多余的 }".to_string()),
+            c => text.push(c),
+        }
+    }
+    if optional.is_some() {
+        return Err("缺少 ]".to_string());
+    }
+    flush(&mut text, &mut root, &mut optional);
+    Ok(root)
+}
+
+fn render(segments: &[Segment], value: &dyn Fn(&str) -> String) -> String {
+    let mut output = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => output.push_str(text),
+            Segment::Field(name) => output.push_str(&value(name)),
+            Segment::Optional(inner) => {
+                let complete = inner.iter().all(|s| match s {
+                    Segment::Field(name) => !value(name).is_empty(),
+                    _ => true,
+                });
+                if complete {
+                    output.push_str(&render(inner, value));
+                }
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frieren() -> (MalEntry<'static>, Vec<CandidateEntry<'static>>) {
+        let mal = MalEntry {
+            title: "Sousou no Frieren 2nd Season",
+            title_ja: Some("葬送のフリーレン 第2期"),
+            media_type: Some("tv"),
+            start_date: Some("2026-01-16"),
+            num_episodes: Some(10),
+            studios: vec!["Madhouse"],
+        };
+        let candidates = vec![
+            CandidateEntry {
+                id: 400602,
+                name: "葬送のフリーレン 第2期",
+                name_cn: Some("葬送的芙莉莲 第二季"),
+                date: Some("2026-01-16"),
+                eps: Some(10),
+                platform: Some("TV"),
+                studios: vec!["マッドハウス".to_string()],
+                studio_match: Some(true),
+            },
+            CandidateEntry {
+                id: 1,
+                name: "x",
+                ..Default::default()
+            },
+        ];
+        (mal, candidates)
+    }
+
+    #[test]
+    fn test_system_prompt_is_compact() {
+        // 确保系统提示足够精简（中文 UTF-8 约 3 字节/字）
+        let builtin = PromptTemplate::builtin();
+        assert_eq!(builtin.version(), "builtin-2");
+        assert!(builtin.system().len() < 150);
+        assert!(builtin.batch_system().len() < 180);
+    }
+
+    #[test]
+    fn test_format_match_input() {
+        let (mal, candidates) = frieren();
+        let template = PromptTemplate::builtin();
+
+        assert_eq!(
+            template.format_input(&mal, &candidates, PromptContext::Full),
+            "MAL:Sousou no Frieren 2nd Season|葬送のフリーレン 第2期(tv,2026-01-16,10ep,Madhouse)\nBGM:\n400602:葬送のフリーレン 第2期|葬送的芙莉莲 第二季(TV,2026-01-16,10ep,マッドハウス,制作一致)\n1:x"
+        );
+        // 只有标题时与旧格式一致
+        assert_eq!(
+            template.format_input(&mal, &candidates, PromptContext::Titles),
+            "MAL:Sousou no Frieren 2nd Season|葬送のフリーレン 第2期\nBGM:\n400602:葬送のフリーレン 第2期|葬送的芙莉莲 第二季\n1:x"
+        );
+
+        // 未知集数（MAL 为 0）不输出
+        let mal = MalEntry {
+            title: "Test",
+            num_episodes: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            template.format_input(&mal, &[], PromptContext::Full),
+            "MAL:Test\nBGM:"
+        );
+    }
+
+    #[test]
+    fn test_custom_template() {
+        let template = PromptTemplate::parse(
+            "version = v2\n--- system\n系统\n多行\n--- batch_system\n批量\n--- mal\n\
+             {title}[ 类型:{media_type}][ {episodes}集]\n--- candidate\n- {id} {name}[ ({date})]\n",
+        )
+        .unwrap();
+        assert_eq!(template.version(), "v2");
+        assert_eq!(template.system(), "系统\n多行");
+
+        let (mal, candidates) = frieren();
+        assert_eq!(
+            template.format_input(&mal, &candidates, PromptContext::Full),
+            "Sousou no Frieren 2nd Season 类型:tv 10集\nBGM:\n- 400602 葬送のフリーレン 第2期 (2026-01-16)\n- 1 x"
+        );
+    }
+
+    #[test]
+    fn test_invalid_template() {
+        let parse = |mal: &str| {
+            PromptTemplate::parse(&format!(
+                "version = v2\n--- system\na\n--- batch_system\nb\n--- mal\n{}\n--- candidate\n{{id}}",
+                mal
+            ))
+        };
+        assert!(parse("{title}").is_ok());
+        assert!(matches!(parse("{name}"), Err(LlmError::Template(_))));
+        assert!(parse("[{title}").is_err());
+        assert!(parse("{title}]").is_err());
+        assert!(parse("[[{title}]]").is_err());
+        assert!(parse("{title").is_err());
+        assert!(parse("{title}\n第二行").is_err());
+
+        // 缺少版本号或分节
+        assert!(PromptTemplate::parse("--- system\na").is_err());
+        assert!(PromptTemplate::parse("version = v2\n--- system\na").is_err());
+        assert!(PromptTemplate::parse("version = v2\n--- other\na").is_err());
+    }
+}
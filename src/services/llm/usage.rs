@@ -0,0 +1,162 @@
+use super::Usage;
+
+/// 每百万 token 的价格（币种由配置决定，只用于估算）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LlmPricing {
+    /// 输入（缓存未命中，或服务商不区分缓存时的全部输入）
+    pub prompt: f64,
+    /// 输入（缓存命中）
+    pub prompt_cache_hit: f64,
+    /// 输出
+    pub completion: f64,
+}
+
+impl LlmPricing {
+    /// 从环境变量读取价格
+    ///
+    /// - `LLM_PRICE_INPUT`：输入（缓存未命中）
+    /// - `LLM_PRICE_CACHE_HIT`：输入（缓存命中），默认与 `LLM_PRICE_INPUT` 相同
+    /// - `LLM_PRICE_OUTPUT`：输出
+    pub fn from_env() -> Self {
+        Self::from_env_prefixed("LLM_PRICE")
+    }
+
+    /// 共识模式第二个模型的价格：`LLM_CONSENSUS_PRICE_INPUT` / `_CACHE_HIT` / `_OUTPUT`
+    pub fn consensus_from_env() -> Self {
+        Self::from_env_prefixed("LLM_CONSENSUS_PRICE")
+    }
+
+    fn from_env_prefixed(prefix: &str) -> Self {
+        let var = |name: &str| {
+            std::env::var(format!("{}_{}", prefix, name))
+                .ok()
+                .and_then(|v| v.trim().parse::<f64>().ok())
+        };
+
+        let prompt = var("INPUT").unwrap_or_default();
+        Self {
+            prompt,
+            prompt_cache_hit: var("CACHE_HIT").unwrap_or(prompt),
+            completion: var("OUTPUT").unwrap_or_default(),
+        }
+    }
+
+    /// 是否配置了价格
+    pub fn is_set(&self) -> bool {
+        self.prompt > 0.0 || self.prompt_cache_hit > 0.0 || self.completion > 0.0
+    }
+
+    /// 估算单次请求费用
+    ///
+    /// 服务商未返回缓存命中信息时，全部输入按未命中计价
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        let (hit, miss) = split_prompt_tokens(usage);
+        (hit as f64 * self.prompt_cache_hit
+            + miss as f64 * self.prompt
+            + usage.completion_tokens as f64 * self.completion)
+            / 1_000_000.0
+    }
+}
+
+/// 拆分输入 token 为（缓存命中，缓存未命中）
+fn split_prompt_tokens(usage: &Usage) -> (u32, u32) {
+    match (
+        usage.prompt_cache_hit_tokens,
+        usage.prompt_cache_miss_tokens,
+    ) {
+        (Some(hit), Some(miss)) => (hit, miss),
+        (Some(hit), None) => (hit, usage.prompt_tokens.saturating_sub(hit)),
+        (None, Some(miss)) => (usage.prompt_tokens.saturating_sub(miss), miss),
+        (None, None) => (0, usage.prompt_tokens),
+    }
+}
+
+/// 一次运行累计的 token 用量与费用
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageStats {
+    pub requests: u32,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cache_hit_tokens: u64,
+    pub cache_miss_tokens: u64,
+    pub cost: f64,
+}
+
+impl UsageStats {
+    /// 累加一次请求的用量
+    pub fn record(&mut self, usage: &Usage, pricing: &LlmPricing) {
+        let (hit, miss) = split_prompt_tokens(usage);
+        self.requests += 1;
+        self.prompt_tokens += usage.prompt_tokens as u64;
+        self.completion_tokens += usage.completion_tokens as u64;
+        self.cache_hit_tokens += hit as u64;
+        self.cache_miss_tokens += miss as u64;
+        self.cost += pricing.cost(usage);
+    }
+
+    /// 缓存命中率
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        let total = self.cache_hit_tokens + self.cache_miss_tokens;
+        (total > 0).then(|| self.cache_hit_tokens as f64 / total as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(prompt: u32, completion: u32, hit: Option<u32>, miss: Option<u32>) -> Usage {
+        Usage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: prompt + completion,
+            prompt_cache_hit_tokens: hit,
+            prompt_cache_miss_tokens: miss,
+        }
+    }
+
+    #[test]
+    fn test_pricing_cost() {
+        let pricing = LlmPricing {
+            prompt: 2.0,
+            prompt_cache_hit: 0.5,
+            completion: 8.0,
+        };
+
+        // DeepSeek：区分缓存命中
+        let cost = pricing.cost(&usage(1_000_000, 500_000, Some(800_000), Some(200_000)));
+        assert!((cost - (0.4 + 0.4 + 4.0)).abs() < 1e-9);
+
+        // 其他服务商：全部按未命中计价
+        let cost = pricing.cost(&usage(1_000_000, 0, None, None));
+        assert!((cost - 2.0).abs() < 1e-9);
+
+        assert!(!LlmPricing::default().is_set());
+        assert_eq!(
+            LlmPricing::default().cost(&usage(100, 100, None, None)),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_usage_stats_record() {
+        let pricing = LlmPricing {
+            prompt: 1.0,
+            prompt_cache_hit: 1.0,
+            completion: 1.0,
+        };
+        let mut stats = UsageStats::default();
+        assert!(stats.cache_hit_ratio().is_none());
+
+        stats.record(&usage(100, 10, Some(80), Some(20)), &pricing);
+        stats.record(&usage(100, 10, None, None), &pricing);
+
+        assert_eq!(stats.requests, 2);
+        assert_eq!(stats.prompt_tokens, 200);
+        assert_eq!(stats.completion_tokens, 20);
+        assert_eq!(stats.cache_hit_tokens, 80);
+        assert_eq!(stats.cache_miss_tokens, 120);
+        assert!((stats.cache_hit_ratio().unwrap() - 0.4).abs() < 1e-9);
+        assert!((stats.cost - 220.0 / 1_000_000.0).abs() < 1e-12);
+    }
+}
@@ -1,8 +1,12 @@
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::services::cache::{CacheError, ResponseCache};
+
 const BASE_URL: &str = "https://api.myanimelist.net/v2";
+const CACHE_NAMESPACE: &str = "mal";
 
 /// 请求的字段列表
 const FIELDS: &str = "id,title,alternative_titles,start_date,end_date,synopsis,media_type,status,num_episodes,start_season,broadcast,source,studios,rating";
@@ -13,9 +17,13 @@ pub enum MalError {
     Request(#[from] reqwest::Error),
     #[error("API error: {0}")]
     Api(String),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Cache(#[from] CacheError),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Season {
     Winter,
@@ -35,6 +43,33 @@ impl std::fmt::Display for Season {
     }
 }
 
+impl Season {
+    /// 月份所在的季度（1-3 月为冬季，以此类推）
+    pub fn from_month(month: u32) -> Option<Self> {
+        match month {
+            1..=3 => Some(Season::Winter),
+            4..=6 => Some(Season::Spring),
+            7..=9 => Some(Season::Summer),
+            10..=12 => Some(Season::Fall),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for Season {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "winter" => Ok(Season::Winter),
+            "spring" => Ok(Season::Spring),
+            "summer" => Ok(Season::Summer),
+            "fall" => Ok(Season::Fall),
+            _ => Err(format!("unknown season: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlternativeTitles {
     #[serde(default)]
@@ -103,6 +138,27 @@ pub struct AnimeNode {
     pub rating: Option<String>,
 }
 
+/// 关联作品中的番组（只有基本信息）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedNode {
+    pub id: u64,
+    pub title: String,
+}
+
+/// 关联作品
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedAnime {
+    pub node: RelatedNode,
+    /// `prequel`、`sequel`、`side_story`、`parent_story` 等
+    pub relation_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AnimeRelations {
+    #[serde(default)]
+    related_anime: Vec<RelatedAnime>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnimeEntry {
     pub node: AnimeNode,
@@ -133,6 +189,7 @@ pub struct SeasonalAnimeResponse {
 pub struct MalClient {
     client: Client,
     client_id: String,
+    cache: Option<ResponseCache>,
 }
 
 impl MalClient {
@@ -140,7 +197,70 @@ impl MalClient {
         Self {
             client: Client::new(),
             client_id,
+            cache: None,
+        }
+    }
+
+    /// 设置响应缓存（记录或重放 API 响应）
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// 发送 GET 请求并解析 JSON 响应
+    ///
+    /// 设置了缓存时先按 URL 与查询参数查找，成功的响应会被记录
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, MalError> {
+        let cache_key = ResponseCache::key(&[url, &serde_json::to_string(query)?]);
+        if let Some(cache) = &self.cache
+            && let Some(body) = cache.get(CACHE_NAMESPACE, &cache_key).await?
+        {
+            return Ok(serde_json::from_str(&body)?);
+        }
+
+        let response = self
+            .client
+            .get(url)
+            .header("X-MAL-CLIENT-ID", &self.client_id)
+            .query(query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(MalError::Api(format!("{}: {}", status, text)));
         }
+
+        let body = response.text().await?;
+        let result = serde_json::from_str(&body)?;
+        if let Some(cache) = &self.cache {
+            cache.put(CACHE_NAMESPACE, &cache_key, &body).await;
+        }
+        Ok(result)
+    }
+
+    /// 获取单个番组详情
+    ///
+    /// 字段与季度列表相同
+    pub async fn get_anime(&self, anime_id: u64) -> Result<AnimeNode, MalError> {
+        let url = format!("{}/anime/{}", BASE_URL, anime_id);
+        self.get_json(&url, &[("fields", FIELDS.to_string())]).await
+    }
+
+    /// 获取番组的关联作品（前作、续作等）
+    ///
+    /// `related_anime` 只能在详情接口获取，与 `get_anime` 分开请求以免影响其缓存
+    pub async fn get_related_anime(&self, anime_id: u64) -> Result<Vec<RelatedAnime>, MalError> {
+        let url = format!("{}/anime/{}", BASE_URL, anime_id);
+        let relations: AnimeRelations = self
+            .get_json(&url, &[("fields", "related_anime".to_string())])
+            .await?;
+        Ok(relations.related_anime)
     }
 
     /// 获取指定季度的新番列表
@@ -156,32 +276,18 @@ impl MalClient {
     ) -> Result<SeasonalAnimeResponse, MalError> {
         let url = format!("{}/anime/season/{}/{}", BASE_URL, year, season);
 
-        let mut request = self
-            .client
-            .get(&url)
-            .header("X-MAL-CLIENT-ID", &self.client_id)
-            .query(&[("fields", FIELDS)]);
-
+        let mut query = vec![("fields", FIELDS.to_string())];
         if nsfw {
-            request = request.query(&[("nsfw", "true")]);
+            query.push(("nsfw", "true".to_string()));
         }
         if let Some(limit) = limit {
-            request = request.query(&[("limit", limit.min(500))]);
+            query.push(("limit", limit.min(500).to_string()));
         }
         if let Some(offset) = offset {
-            request = request.query(&[("offset", offset)]);
-        }
-
-        let response = request.send().await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            return Err(MalError::Api(format!("{}: {}", status, text)));
+            query.push(("offset", offset.to_string()));
         }
 
-        let result = response.json::<SeasonalAnimeResponse>().await?;
-        Ok(result)
+        self.get_json(&url, &query).await
     }
 
     /// 获取指定季度的所有新番（自动分页）
@@ -230,6 +336,45 @@ mod tests {
         assert_eq!(Season::Fall.to_string(), "fall");
     }
 
+    #[test]
+    fn test_season_from_str() {
+        for season in [Season::Winter, Season::Spring, Season::Summer, Season::Fall] {
+            assert_eq!(season.to_string().parse::<Season>(), Ok(season));
+        }
+        assert!("autumn".parse::<Season>().is_err());
+    }
+
+    #[test]
+    fn test_season_from_month() {
+        assert_eq!(Season::from_month(3), Some(Season::Winter));
+        assert_eq!(Season::from_month(4), Some(Season::Spring));
+        assert_eq!(Season::from_month(12), Some(Season::Fall));
+        assert_eq!(Season::from_month(0), None);
+    }
+
+    #[test]
+    fn test_deserialize_related_anime() {
+        let json = r#"{
+            "id": 59978,
+            "title": "Sousou no Frieren 2nd Season",
+            "related_anime": [
+                {
+                    "node": { "id": 52991, "title": "Sousou no Frieren", "main_picture": null },
+                    "relation_type": "prequel",
+                    "relation_type_formatted": "Prequel"
+                }
+            ]
+        }"#;
+
+        let relations: AnimeRelations = serde_json::from_str(json).unwrap();
+        assert_eq!(relations.related_anime.len(), 1);
+        assert_eq!(relations.related_anime[0].node.id, 52991);
+        assert_eq!(relations.related_anime[0].relation_type, "prequel");
+
+        let relations: AnimeRelations = serde_json::from_str(r#"{"id": 1}"#).unwrap();
+        assert!(relations.related_anime.is_empty());
+    }
+
     #[test]
     fn test_deserialize_anime_node() {
         let json = r#"{
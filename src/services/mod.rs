@@ -1,3 +1,4 @@
 pub mod bgmtv;
-pub mod ds;
+pub mod cache;
+pub mod llm;
 pub mod mal;